@@ -0,0 +1,48 @@
+use anyhow::Result;
+use shakmaty::{san::San, Chess, Position};
+
+use crate::i18n::Lang;
+use crate::notation;
+use crate::screen::Screen;
+
+/// Offers to explore the final position of a tactic, and if accepted, lets moves be played for
+/// both sides until the user quits.
+pub fn offer(position: Chess, lang: Lang, screen: &mut Screen) -> Result<()> {
+    screen.gap();
+    let reply = screen.prompt(lang.analysis_offer())?;
+    if lang.parse_yes_no(reply.trim()) != Some(true) {
+        return Ok(());
+    }
+    explore(position, lang, screen)
+}
+
+/// A free analysis board. Any legal move can be played for the side to move, and moves can be
+/// taken back, back to the starting position.
+fn explore(start: Chess, lang: Lang, screen: &mut Screen) -> Result<()> {
+    let mut history = vec![start];
+    screen.message(lang.analysis_started().to_string());
+    loop {
+        let position = history.last().unwrap().clone();
+        screen.show_board(&position);
+        screen.gap();
+        let reply = screen.prompt(&lang.analysis_prompt(position.turn()))?;
+        match reply.trim() {
+            "" | "q" | "quit" => return Ok(()),
+            "u" | "undo" => {
+                if history.len() > 1 {
+                    history.pop();
+                } else {
+                    screen.message(lang.nothing_to_undo().to_string());
+                }
+            }
+            input => match notation::parse_move(input, &position, lang) {
+                Some(m) => {
+                    let san = screen.san(&San::from_move(&position, &m).to_string());
+                    screen.message(lang.analysis_move(position.turn(), &san));
+                    history.push(position.play(&m)?);
+                }
+                None => screen.message(lang.illegal_move(input)),
+            },
+        }
+    }
+}
@@ -0,0 +1,806 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    env, fs,
+    path::PathBuf,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use reqwest::{
+    header::{ACCEPT, ETAG, IF_NONE_MATCH, RETRY_AFTER},
+    Certificate, Proxy, Response, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+
+use crate::filter::TagFilter;
+use crate::i18n::Lang;
+use crate::rng::Rng;
+use crate::screen::Screen;
+use crate::Variant;
+
+/// A tactic as sent by the server. Only the id, FEN and moves are required, so that servers
+/// which leave out or rename the other fields still work.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ChessTactic {
+    #[serde(alias = "puzzleId")]
+    pub id: String,
+    #[serde(deserialize_with = "deserialize_moves")]
+    pub moves: Vec<String>,
+    pub fen: String,
+    #[serde(default)]
+    pub popularity: i32,
+    #[serde(default, alias = "themes")]
+    pub tags: Vec<String>,
+    #[serde(default, alias = "gameLink")]
+    pub game_link: String,
+    #[serde(default)]
+    pub rating: i32,
+    #[serde(default, alias = "ratingDeviation")]
+    pub rating_deviation: i32,
+    #[serde(default, alias = "numberPlays", alias = "plays")]
+    pub number_plays: i32,
+    /// Whether the first move is the solver's, instead of the opponent's move leading into the
+    /// tactic like in Lichess puzzles. Servers that don't say are handled by `setup_moves`.
+    #[serde(
+        default,
+        alias = "solverMovesFirst",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub solver_moves_first: Option<bool>,
+}
+
+impl ChessTactic {
+    /// How many moves are played before the solver's first move: the opponent's move leading
+    /// into the tactic, or none if the solver moves first. Unless the server says which it is,
+    /// this goes by the length of the line, since it always ends with the solver's move.
+    pub fn setup_moves(&self) -> usize {
+        let solver_moves_first = self.solver_moves_first.unwrap_or(self.moves.len() % 2 == 1);
+        if solver_moves_first {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+/// Accepts moves either as a list, or as a single space separated string like the Lichess puzzle
+/// database uses.
+fn deserialize_moves<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Moves {
+        List(Vec<String>),
+        Spaced(String),
+    }
+    Ok(match Moves::deserialize(deserializer)? {
+        Moves::List(moves) => moves,
+        Moves::Spaced(moves) => moves.split_whitespace().map(String::from).collect(),
+    })
+}
+
+/// The filters for a tactic. Servers may ignore some of them, so fetched tactics are checked
+/// with `accepts` as well.
+#[derive(Serialize, Debug)]
+pub struct ChessTacticRequest {
+    pub rating_gte: Option<i32>,
+    pub rating_lte: Option<i32>,
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub all_tags: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<TagFilter>,
+    /// Checked against the solution locally, the server only sees the `mateInN` tag in `filter`.
+    #[serde(skip)]
+    pub mate_in: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating_deviation_lte: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub popularity_gte: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_plays_gte: Option<i32>,
+    #[serde(skip_serializing_if = "Variant::is_standard")]
+    pub variant: Variant,
+}
+
+impl ChessTacticRequest {
+    /// Whether a tactic passes the filters, for servers that don't support all of them.
+    pub fn accepts(&self, tactic: &ChessTactic) -> bool {
+        let has_all_tags = self.tags.iter().all(|tag| tactic.tags.contains(tag));
+        let has_excluded_tag = self
+            .exclude_tags
+            .iter()
+            .any(|tag| tactic.tags.contains(tag));
+        (!self.all_tags || has_all_tags)
+            && !has_excluded_tag
+            && self
+                .filter
+                .as_ref()
+                .is_none_or(|filter| filter.matches(&tactic.tags))
+            && self
+                .rating_deviation_lte
+                .is_none_or(|max| tactic.rating_deviation <= max)
+            && self
+                .popularity_gte
+                .is_none_or(|min| tactic.popularity >= min)
+            && self
+                .number_plays_gte
+                .is_none_or(|min| tactic.number_plays >= min)
+    }
+}
+
+/// The version of the tactics API this client speaks, sent to the server so it can keep
+/// answering older clients in the format they expect.
+const API_VERSION: &str = "1";
+const API_VERSION_HEADER: &str = "X-Tactics-Api-Version";
+const RATE_LIMIT_REMAINING_HEADER: &str = "X-RateLimit-Remaining";
+
+/// How many times a rate limited request is retried before giving up.
+const MAX_RETRIES: u32 = 3;
+/// Warn when the server says there are this few requests left before it starts rate limiting.
+const LOW_RATE_LIMIT: u64 = 5;
+const LICHESS_URL: &str = "https://lichess.org";
+/// The most tactics to ask for at once, from servers that can send them in batches.
+const MAX_BATCH_SIZE: usize = 20;
+const SERVER_INFO_FILE: &str = "server.json";
+
+/// The paths of the server's endpoints, relative to its URL. Servers can send their own from
+/// `/api/version`, and each can be overridden with an environment variable, ex.
+/// `TACTICS_TACTIC_PATH`. `{id}` in the report path stands for the tactic's id. There's only a
+/// batch path if the server advertises one or it's set with `TACTICS_BATCH_PATH`.
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+struct Endpoints {
+    tactic: String,
+    batch: Option<String>,
+    report: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Endpoints {
+        Endpoints {
+            tactic: "/api/v1/tactic".to_string(),
+            batch: None,
+            report: "/api/v1/tactic/{id}/report".to_string(),
+        }
+    }
+}
+
+impl Endpoints {
+    fn with_overrides(mut self) -> Endpoints {
+        let paths = [
+            ("TACTICS_TACTIC_PATH", &mut self.tactic),
+            ("TACTICS_REPORT_PATH", &mut self.report),
+        ];
+        for (variable, path) in paths {
+            if let Ok(value) = env::var(variable) {
+                *path = value;
+            }
+        }
+        if let Ok(batch) = env::var("TACTICS_BATCH_PATH") {
+            self.batch = Some(batch).filter(|batch| !batch.is_empty());
+        }
+        self
+    }
+}
+
+/// What a server says about itself from `/api/version`.
+#[derive(Deserialize)]
+struct ServerInfo {
+    #[serde(default)]
+    api_version: Option<String>,
+    #[serde(default)]
+    endpoints: Option<Endpoints>,
+}
+
+/// The last `/api/version` response with its ETag, kept between runs so the server can answer
+/// with 304 Not Modified when nothing changed.
+#[derive(Serialize, Deserialize)]
+struct CachedServerInfo {
+    url: String,
+    etag: String,
+    body: String,
+}
+
+/// A client for the tactics server. It holds on to one connection pool, so fetching tactic
+/// after tactic reuses connections instead of opening a new one each time. Tactics are fetched
+/// one at a time, unless the run needs several and the server has a batch endpoint.
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    endpoints: Endpoints,
+    lang: Lang,
+    /// Fetched tactics that haven't been handed out yet.
+    queue: RefCell<VecDeque<ChessTactic>>,
+    /// How many tactics the run is expected to use, which are fetched together when batching.
+    batch_size: usize,
+    /// Cleared once a batch request fails, so the single endpoint is used from then on.
+    batches: Cell<bool>,
+    /// Picks the seed sent with each request, if the run is seeded.
+    seeds: RefCell<Option<Rng>>,
+    /// Where the server's `/api/version` response is cached, if anywhere.
+    cache_dir: Option<PathBuf>,
+}
+
+/// The server to fetch tactics from, which can be changed with `TACTICS_SERVER_URL`, ex. to run
+/// against a local server.
+pub fn server_url() -> String {
+    env::var("TACTICS_SERVER_URL").unwrap_or_else(|_| "https://chessmadra.com".to_string())
+}
+
+impl Client {
+    /// Creates a client that connects through the given proxy, if there is one. Otherwise the
+    /// `HTTPS_PROXY` and `HTTP_PROXY` environment variables are used. Extra root certificates, ex.
+    /// for a corporate network, can be given as a PEM bundle in `TACTICS_CA_BUNDLE`. With a seed,
+    /// every request carries a seed derived from it, so servers can pick tactics reproducibly.
+    pub fn new(
+        base_url: String,
+        lang: Lang,
+        proxy: Option<&str>,
+        seed: Option<u64>,
+    ) -> Result<Client> {
+        let mut builder = reqwest::Client::builder().user_agent("tactics-trainer-cli");
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(
+                Proxy::all(proxy).with_context(|| format!("Couldn't use {} as a proxy", proxy))?,
+            );
+        }
+        if let Some(path) = env::var_os("TACTICS_CA_BUNDLE") {
+            let pem = fs::read_to_string(&path)
+                .with_context(|| format!("Couldn't read the CA bundle at {:?}", path))?;
+            let certificates = pem_certificates(&pem);
+            if certificates.is_empty() {
+                bail!(
+                    "The CA bundle at {:?} doesn't contain any certificates",
+                    path
+                );
+            }
+            for certificate in certificates {
+                let certificate =
+                    Certificate::from_pem(certificate.as_bytes()).with_context(|| {
+                        format!("The CA bundle at {:?} isn't a valid PEM file", path)
+                    })?;
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        let http = builder.build()?;
+        let endpoints = Endpoints::default().with_overrides();
+        Ok(Client {
+            http,
+            base_url,
+            lang,
+            queue: RefCell::new(VecDeque::new()),
+            endpoints,
+            batch_size: 1,
+            batches: Cell::new(true),
+            seeds: RefCell::new(seed.map(|seed| Rng::new(Some(seed)))),
+            cache_dir: None,
+        })
+    }
+
+    /// Caches the server's `/api/version` response in the directory, so later runs only send a
+    /// conditional request for it.
+    pub fn set_cache_dir(&mut self, dir: PathBuf) {
+        self.cache_dir = Some(dir);
+    }
+
+    /// Asks the server for its API version and endpoints, for self-hosted servers whose routes
+    /// differ from the defaults. Servers without `/api/version` keep the defaults. A cached
+    /// response is revalidated with its ETag instead of being fetched again.
+    pub async fn discover(&mut self, screen: &mut Screen) {
+        let cached = self.cached_server_info();
+        let mut builder = self
+            .http
+            .get(format!("{}/api/version", self.base_url))
+            .header(ACCEPT, "application/json")
+            .header(API_VERSION_HEADER, API_VERSION);
+        if let Some(cached) = &cached {
+            builder = builder.header(IF_NONE_MATCH, &cached.etag);
+        }
+        let response = match builder.send().await {
+            Ok(response) => response,
+            Err(_) => return,
+        };
+        let body = match (response.status(), cached) {
+            (StatusCode::NOT_MODIFIED, Some(cached)) => cached.body,
+            (status, _) if status.is_success() => {
+                let etag = response
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                    .map(|etag| etag.to_string());
+                let body = match response.text().await {
+                    Ok(body) => body,
+                    Err(_) => return,
+                };
+                if let Some(etag) = etag {
+                    self.cache_server_info(etag, &body);
+                }
+                body
+            }
+            _ => return,
+        };
+        let info: ServerInfo = match serde_json::from_str(&body) {
+            Ok(info) => info,
+            Err(_) => return,
+        };
+        if let Some(version) = info.api_version.filter(|version| version != API_VERSION) {
+            screen.message(self.lang.server_version_mismatch(&version, API_VERSION));
+        }
+        if let Some(endpoints) = info.endpoints {
+            self.endpoints = endpoints.with_overrides();
+        }
+    }
+
+    fn cached_server_info(&self) -> Option<CachedServerInfo> {
+        let path = self.cache_dir.as_ref()?.join(SERVER_INFO_FILE);
+        let cached: CachedServerInfo =
+            serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+        Some(cached).filter(|cached| cached.url == self.base_url)
+    }
+
+    /// Saves the response for the next run. This is best effort, a missing cache only means the
+    /// next run fetches it in full.
+    fn cache_server_info(&self, etag: String, body: &str) {
+        let path = match &self.cache_dir {
+            Some(dir) => dir.join(SERVER_INFO_FILE),
+            None => return,
+        };
+        let cached = CachedServerInfo {
+            url: self.base_url.clone(),
+            etag,
+            body: body.to_string(),
+        };
+        if let Ok(json) = serde_json::to_string(&cached) {
+            let _ = fs::write(path, json);
+        }
+    }
+
+    /// Sets how many tactics the run will use, so that servers with a batch endpoint can send
+    /// them in one request. Nothing is batched for runs that only use one.
+    pub fn set_batch_size(&mut self, size: usize) {
+        self.batch_size = size.min(MAX_BATCH_SIZE);
+    }
+
+    fn tactic_endpoint(&self) -> String {
+        format!("{}{}", self.base_url, self.endpoints.tactic)
+    }
+
+    /// Gets a tactic matching the request, from the last batch if there are any left.
+    pub async fn get_tactic(
+        &self,
+        request: &ChessTacticRequest,
+        screen: &mut Screen,
+    ) -> Result<ChessTactic> {
+        if let Some(tactic) = self.queue.borrow_mut().pop_front() {
+            return Ok(tactic);
+        }
+        if let Some(batch) = &self.endpoints.batch {
+            if self.batch_size > 1 && self.batches.get() {
+                match self.get_batch(batch, request, screen).await {
+                    Ok(tactic) => return Ok(tactic),
+                    // Any problem with a batch falls back to fetching tactics one at a time
+                    Err(_) => self.batches.set(false),
+                }
+            }
+        }
+        let response = self.send(&self.tactic_endpoint(), request, screen).await?;
+        read(response, "tactic").await
+    }
+
+    /// Fetches a batch of tactics, returning the first and queueing the rest.
+    async fn get_batch(
+        &self,
+        path: &str,
+        request: &ChessTacticRequest,
+        screen: &mut Screen,
+    ) -> Result<ChessTactic> {
+        let url = format!("{}{}?count={}", self.base_url, path, self.batch_size);
+        let response = self.send(&url, request, screen).await?;
+        let tactics: Vec<ChessTactic> = read(response, "tactics").await?;
+        let mut queue = self.queue.borrow_mut();
+        queue.extend(tactics);
+        queue
+            .pop_front()
+            .ok_or_else(|| anyhow!("The server didn't send any tactics"))
+    }
+
+    /// The moves of a Lichess game in SAN, separated by spaces.
+    pub async fn lichess_game_moves(&self, game_id: &str) -> Result<String> {
+        #[derive(Deserialize)]
+        struct Game {
+            moves: String,
+        }
+        let response = self
+            .http
+            .get(format!("{}/game/export/{}", LICHESS_URL, game_id))
+            .header(ACCEPT, "application/json")
+            .send()
+            .await?;
+        let game: Game = read(response, "game").await?;
+        Ok(game.moves)
+    }
+
+    /// Tells the server that a tactic can't be played through, so it can be fixed. This is best
+    /// effort, since servers may not have the endpoint.
+    pub async fn report_broken(&self, id: &str, reason: &str) {
+        let _ = self
+            .http
+            .post(format!(
+                "{}{}",
+                self.base_url,
+                self.endpoints.report.replace("{id}", id)
+            ))
+            .header(API_VERSION_HEADER, API_VERSION)
+            .json(&serde_json::json!({ "reason": reason }))
+            .send()
+            .await;
+    }
+
+    /// Sends the request, waiting and retrying when the server rate limits it.
+    async fn send(
+        &self,
+        url: &str,
+        request: &ChessTacticRequest,
+        screen: &mut Screen,
+    ) -> Result<Response> {
+        let mut retries = 0;
+        let seed = self.seeds.borrow_mut().as_mut().map(|rng| rng.next_u64());
+        let response = loop {
+            let mut builder = self.http.post(url);
+            if let Some(seed) = seed {
+                builder = builder.query(&[("seed", seed)]);
+            }
+            let response = builder
+                .header(ACCEPT, "application/json")
+                .header(API_VERSION_HEADER, API_VERSION)
+                .json(request)
+                .send()
+                .await?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                break response;
+            }
+            let wait = header_number(&response, RETRY_AFTER.as_str())
+                .map(Duration::from_secs)
+                .unwrap_or_else(|| Duration::from_secs(1 << retries));
+            if retries == MAX_RETRIES {
+                bail!(
+                    "The server is rate limiting requests, try again in {} seconds",
+                    wait.as_secs()
+                );
+            }
+            screen.message(self.lang.rate_limited(wait.as_secs()));
+            tokio::time::sleep(wait).await;
+            retries += 1;
+        };
+        if let Some(remaining) = header_number(&response, RATE_LIMIT_REMAINING_HEADER) {
+            if remaining <= LOW_RATE_LIMIT {
+                screen.message(self.lang.rate_limit_low(remaining));
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// Reads a JSON response, ex. a tactic or a batch of tactics, with errors that describe what was
+/// being read.
+async fn read<T: DeserializeOwned>(response: Response, what: &str) -> Result<T> {
+    let status = response.status();
+    let server_version = response
+        .headers()
+        .get(API_VERSION_HEADER)
+        .and_then(|version| version.to_str().ok())
+        .map(|version| version.to_string());
+    let body = response.text().await?;
+    if !status.is_success() {
+        bail!("The server responded with {}: {}", status, excerpt(&body));
+    }
+    if !body
+        .trim_start()
+        .starts_with(|c: char| c == '{' || c == '[')
+    {
+        bail!("The server didn't respond with JSON: {}", excerpt(&body));
+    }
+    let value: T = serde_json::from_str(&body).map_err(|err| {
+        let mut message = format!("Couldn't read the {} the server sent ({})", what, err);
+        if let Some(version) = server_version.filter(|version| version != API_VERSION) {
+            message.push_str(&format!(
+                ". The server speaks version {} of the API, but this client expects version {}, \
+                 so try updating tactics-trainer",
+                version, API_VERSION
+            ));
+        }
+        anyhow!(message)
+    })?;
+    Ok(value)
+}
+
+/// Splits a PEM bundle into its certificates, since `Certificate::from_pem` only reads the first
+/// one and bundles often have intermediates or several roots.
+fn pem_certificates(pem: &str) -> Vec<&str> {
+    const END: &str = "-----END CERTIFICATE-----";
+    let mut certificates = vec![];
+    let mut rest = pem;
+    while let Some(start) = rest.find("-----BEGIN CERTIFICATE-----") {
+        match rest[start..].find(END) {
+            Some(end) => {
+                let end = start + end + END.len();
+                certificates.push(&rest[start..end]);
+                rest = &rest[end..];
+            }
+            None => break,
+        }
+    }
+    certificates
+}
+
+fn header_number(response: &Response, name: &str) -> Option<u64> {
+    response
+        .headers()
+        .get(name)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// The start of a response body, for error messages.
+fn excerpt(body: &str) -> String {
+    let body = body.trim();
+    if body.chars().count() > 200 {
+        format!("{}...", body.chars().take(200).collect::<String>())
+    } else {
+        body.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mock_server::{MockServer, Reply, Request};
+    use crate::screen::testing::screen;
+
+    fn client(server: &MockServer) -> Client {
+        Client::new(server.base_url(), Lang::En, None, None).unwrap()
+    }
+
+    fn request() -> ChessTacticRequest {
+        ChessTacticRequest {
+            rating_gte: Some(1200),
+            rating_lte: Some(1800),
+            tags: vec!["fork".to_string()],
+            all_tags: false,
+            filter: Some("fork & !mateIn1".parse().unwrap()),
+            mate_in: None,
+            exclude_tags: vec![],
+            rating_deviation_lte: None,
+            popularity_gte: Some(80),
+            number_plays_gte: None,
+            variant: Variant::Standard,
+        }
+    }
+
+    fn lichess_tactic() -> serde_json::Value {
+        json!({
+            "puzzleId": "00sHx",
+            "fen": "q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17",
+            "moves": "e8d7 a2e6 d7d8 f7f8",
+            "rating": 1760,
+            "themes": ["mate", "mateIn2", "middlegame", "short"]
+        })
+    }
+
+    #[tokio::test]
+    async fn fetches_single_tactics() {
+        let (mut screen, _) = screen(&[]);
+        let server = MockServer::start().await;
+        let single = server.mock(
+            Request::post("/api/v1/tactic"),
+            Reply::status(200).json_body(lichess_tactic()),
+        );
+        let tactic = client(&server)
+            .get_tactic(&request(), &mut screen)
+            .await
+            .unwrap();
+        assert_eq!(single.hits(), 1);
+        assert_eq!(tactic.id, "00sHx");
+        assert_eq!(tactic.moves, vec!["e8d7", "a2e6", "d7d8", "f7f8"]);
+        assert_eq!(tactic.tags.len(), 4);
+        assert_eq!(tactic.setup_moves(), 1);
+    }
+
+    /// A client for a server that advertises a batch endpoint, for a run that uses two tactics.
+    async fn batching_client(server: &MockServer) -> Client {
+        let (mut screen, _) = screen(&[]);
+        server.mock(
+            Request::get("/api/version"),
+            Reply::status(200).json_body(json!({
+                "api_version": API_VERSION,
+                "endpoints": { "batch": "/api/v1/tactics" }
+            })),
+        );
+        let mut client = client(server);
+        client.discover(&mut screen).await;
+        client.set_batch_size(2);
+        client
+    }
+
+    #[tokio::test]
+    async fn hands_out_batches_one_at_a_time() {
+        let (mut screen, _) = screen(&[]);
+        let server = MockServer::start().await;
+        let batch = server.mock(
+            Request::post("/api/v1/tactics").query_param("count", "2"),
+            Reply::status(200).json_body(json!([lichess_tactic(), lichess_tactic()])),
+        );
+        let client = batching_client(&server).await;
+        client.get_tactic(&request(), &mut screen).await.unwrap();
+        client.get_tactic(&request(), &mut screen).await.unwrap();
+        assert_eq!(batch.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_batches_fail() {
+        let (mut screen, _) = screen(&[]);
+        let server = MockServer::start().await;
+        let batch = server.mock(
+            Request::post("/api/v1/tactics"),
+            Reply::status(200).body("<html>Not here</html>"),
+        );
+        let single = server.mock(
+            Request::post("/api/v1/tactic"),
+            Reply::status(200).json_body(lichess_tactic()),
+        );
+        let client = batching_client(&server).await;
+        client.get_tactic(&request(), &mut screen).await.unwrap();
+        client.get_tactic(&request(), &mut screen).await.unwrap();
+        assert_eq!(batch.hits(), 1);
+        assert_eq!(single.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn sends_filters() {
+        let (mut screen, _) = screen(&[]);
+        let server = MockServer::start().await;
+        let single = server.mock(
+            Request::post("/api/v1/tactic").json_body(json!({
+                "rating_gte": 1200,
+                "rating_lte": 1800,
+                "tags": ["fork"],
+                "filter": "(fork & !mateIn1)",
+                "popularity_gte": 80
+            })),
+            Reply::status(200).json_body(lichess_tactic()),
+        );
+        client(&server)
+            .get_tactic(&request(), &mut screen)
+            .await
+            .unwrap();
+        assert_eq!(single.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn uses_discovered_endpoints() {
+        let (mut screen, _) = screen(&[]);
+        let server = MockServer::start().await;
+        server.mock(
+            Request::get("/api/version"),
+            Reply::status(200).json_body(json!({
+                "api_version": API_VERSION,
+                "endpoints": { "tactic": "/puzzles/next" }
+            })),
+        );
+        let single = server.mock(
+            Request::post("/puzzles/next"),
+            Reply::status(200).json_body(lichess_tactic()),
+        );
+        let mut client = client(&server);
+        client.discover(&mut screen).await;
+        client.get_tactic(&request(), &mut screen).await.unwrap();
+        assert_eq!(single.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn shows_server_version_mismatches() {
+        let (mut screen, output) = screen(&[]);
+        let server = MockServer::start().await;
+        server.mock(
+            Request::get("/api/version"),
+            Reply::status(200).json_body(json!({ "api_version": "2" })),
+        );
+        client(&server).discover(&mut screen).await;
+        assert!(output.borrow().contains("version 2 of the API"));
+    }
+
+    #[tokio::test]
+    async fn revalidates_cached_endpoints_with_etags() {
+        let (mut screen, _) = screen(&[]);
+        let dir = env::temp_dir().join(format!("tactics-trainer-etag-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let server = MockServer::start().await;
+        let fresh = server.mock(
+            Request::get("/api/version"),
+            Reply::status(200)
+                .header("ETag", "\"v1\"")
+                .json_body(json!({
+                    "api_version": API_VERSION,
+                    "endpoints": { "tactic": "/puzzles/next" }
+                })),
+        );
+        let mut first_run = client(&server);
+        first_run.set_cache_dir(dir.clone());
+        first_run.discover(&mut screen).await;
+        assert_eq!(fresh.hits(), 1);
+        fresh.delete();
+
+        let not_modified = server.mock(
+            Request::get("/api/version").header("if-none-match", "\"v1\""),
+            Reply::status(304),
+        );
+        let single = server.mock(
+            Request::post("/puzzles/next"),
+            Reply::status(200).json_body(lichess_tactic()),
+        );
+        let mut second_run = client(&server);
+        second_run.set_cache_dir(dir.clone());
+        second_run.discover(&mut screen).await;
+        second_run
+            .get_tactic(&request(), &mut screen)
+            .await
+            .unwrap();
+        assert_eq!(not_modified.hits(), 1);
+        assert_eq!(single.hits(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    async fn error_for(status: u16, body: &'static str) -> String {
+        let (mut screen, _) = screen(&[]);
+        let server = MockServer::start().await;
+        server.mock(
+            Request::post("/api/v1/tactic"),
+            Reply::status(status).body(body),
+        );
+        let result = client(&server).get_tactic(&request(), &mut screen).await;
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn splits_pem_bundles() {
+        let bundle = "subject=Root\n-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n\
+                      -----BEGIN CERTIFICATE-----\nBBBB\n-----END CERTIFICATE-----\n";
+        assert_eq!(
+            pem_certificates(bundle),
+            vec![
+                "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----",
+                "-----BEGIN CERTIFICATE-----\nBBBB\n-----END CERTIFICATE-----",
+            ]
+        );
+        assert!(pem_certificates("not a certificate").is_empty());
+    }
+
+    #[tokio::test]
+    async fn reports_server_errors() {
+        let error = error_for(500, "Internal Server Error").await;
+        assert!(error.contains("500"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn reports_empty_bodies() {
+        let error = error_for(200, "").await;
+        assert!(error.contains("didn't respond with JSON"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn reports_malformed_tactics() {
+        let error = error_for(200, r#"{"id": 1}"#).await;
+        assert!(error.contains("Couldn't read the tactic"), "{}", error);
+    }
+}
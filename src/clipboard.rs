@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+/// The system clipboard, opened on the first copy and kept for the rest of the session. On X11
+/// and Wayland the copied text is handed out by whoever holds the clipboard, so closing it right
+/// after copying can lose the text.
+#[derive(Default)]
+pub struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    /// Puts text on the system clipboard.
+    pub fn copy(&mut self, text: String) -> Result<()> {
+        let clipboard = match &mut self.inner {
+            Some(clipboard) => clipboard,
+            None => self.inner.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)?;
+        Ok(())
+    }
+}
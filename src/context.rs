@@ -0,0 +1,71 @@
+use std::num::NonZeroU32;
+
+use anyhow::Result;
+use shakmaty::{
+    fen::{self, Fen},
+    san::San,
+    uci::Uci,
+    CastlingMode, Chess, Color, Position, Setup,
+};
+
+use crate::api::{self, ChessTactic};
+
+/// How many of the game's moves before the tactic to show.
+const CONTEXT_PLIES: usize = 5;
+
+/// The moves of the game that led to the board the solver sees, which is the tactic's position
+/// after its setup moves, ex. "22. Nf3 Bd7 23. Qe2 O-O 24. a3", or `None` if the tactic doesn't
+/// link to a Lichess game.
+pub async fn lead_up(client: &api::Client, tactic: &ChessTactic) -> Result<Option<String>> {
+    let game_id = match lichess_game_id(&tactic.game_link) {
+        Some(game_id) => game_id,
+        None => return Ok(None),
+    };
+    let moves = client.lichess_game_moves(game_id).await?;
+    let setup: Fen = tactic.fen.parse()?;
+    let target = fen::epd(&setup.position::<Chess>(CastlingMode::Standard)?);
+    let mut position = Chess::default();
+    let mut played = vec![];
+    for san in moves.split_whitespace() {
+        let m = san.parse::<San>()?.to_move(&position)?;
+        played.push((position.fullmoves(), position.turn(), san.to_string()));
+        position = position.play(&m)?;
+        if fen::epd(&position) == target {
+            for uci in &tactic.moves[..tactic.setup_moves()] {
+                let m = uci.parse::<Uci>()?.to_move(&position)?;
+                let san = San::from_move(&position, &m).to_string();
+                played.push((position.fullmoves(), position.turn(), san));
+                position = position.play(&m)?;
+            }
+            let start = played.len().saturating_sub(CONTEXT_PLIES);
+            return Ok(Some(movetext(&played[start..])));
+        }
+    }
+    bail!("the tactic's position doesn't come up in the game")
+}
+
+/// The id in a game link like `https://lichess.org/787zsVup/black#48`.
+fn lichess_game_id(link: &str) -> Option<&str> {
+    let path = link
+        .strip_prefix("https://lichess.org/")
+        .or_else(|| link.strip_prefix("http://lichess.org/"))?;
+    let id = path
+        .split(|c: char| c == '/' || c == '#' || c == '?')
+        .next()?;
+    // Links can use the 12 character id for one player's side, which starts with the game's id
+    id.get(..8)
+}
+
+/// Numbered moves, starting with the move number for a black move.
+fn movetext(moves: &[(NonZeroU32, Color, String)]) -> String {
+    moves
+        .iter()
+        .enumerate()
+        .map(|(i, (number, turn, san))| match turn {
+            Color::White => format!("{}. {}", number, san),
+            Color::Black if i == 0 => format!("{}... {}", number, san),
+            Color::Black => san.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
@@ -0,0 +1,61 @@
+use std::{thread, time::Duration};
+
+use anyhow::Result;
+use shakmaty::{san::San, uci::Uci, Chess, Position};
+
+use crate::api::ChessTactic;
+use crate::i18n::Lang;
+use crate::screen::Screen;
+
+/// How much faster each character is typed than the pause before a move.
+const KEYSTROKES_PER_PAUSE: u32 = 8;
+
+/// Plays through the tactic's solution as if someone were solving it, typing each move at the
+/// prompt with a pause of `delay` before every move.
+pub fn play(
+    tactic: &ChessTactic,
+    mut position: Chess,
+    lang: Lang,
+    screen: &mut Screen,
+    delay: Duration,
+) -> Result<()> {
+    let their_side = !position.turn();
+    let mut moves = tactic.moves.iter().skip(tactic.setup_moves());
+    while let Some(uci) = moves.next() {
+        screen.gap();
+        let m = uci.parse::<Uci>()?.to_move(&position)?;
+        let san = screen.san(&San::from_move(&position, &m).to_string());
+        type_move(screen, &lang.prompt(position.turn()), &san, delay);
+        screen.gap();
+        position = position.play(&m)?;
+        match moves.next() {
+            Some(uci) => {
+                let response = uci.parse::<Uci>()?.to_move(&position)?;
+                let response_san = screen.san(&San::from_move(&position, &response).to_string());
+                screen.message(format!(
+                    "{} {}",
+                    lang.correct(),
+                    lang.responds_with(their_side, &response_san)
+                ));
+                position = position.play(&response)?;
+                screen.update_board(&position);
+            }
+            None => {
+                screen.update_board(&position);
+                screen.message(format!("{} {}", lang.correct(), lang.completed()));
+            }
+        }
+    }
+    thread::sleep(delay);
+    Ok(())
+}
+
+fn type_move(screen: &Screen, prompt: &str, san: &str, delay: Duration) {
+    screen.write(prompt);
+    thread::sleep(delay);
+    for c in san.chars() {
+        screen.write(&c.to_string());
+        thread::sleep(delay / KEYSTROKES_PER_PAUSE);
+    }
+    screen.write("\n");
+}
@@ -0,0 +1,56 @@
+use shakmaty::{Chess, Color, Position, Role, Square};
+
+use crate::i18n::Lang;
+
+const ROLES: [Role; 6] = [
+    Role::King,
+    Role::Queen,
+    Role::Rook,
+    Role::Bishop,
+    Role::Knight,
+    Role::Pawn,
+];
+
+/// Describes every piece on the board in words, for use with a screen reader.
+pub fn describe_position(position: &Chess, lang: Lang) -> String {
+    let board = position.board();
+    let sides = [Color::White, Color::Black]
+        .iter()
+        .map(|&color| {
+            let pieces = ROLES
+                .iter()
+                .flat_map(|&role| {
+                    (board.by_color(color) & board.by_role(role))
+                        .into_iter()
+                        .map(move |square| format!("{} {}", capitalize(lang.role(role)), square))
+                })
+                .collect::<Vec<String>>();
+            format!("{}: {}", lang.side(color), pieces.join(", "))
+        })
+        .collect::<Vec<String>>();
+    format!(
+        "{}; {}",
+        sides.join("; "),
+        lang.side_to_move(position.turn())
+    )
+}
+
+pub fn describe_square(position: &Chess, square: Square, lang: Lang) -> String {
+    match position.board().piece_at(square) {
+        Some(piece) => format!(
+            "{}: {} ({})",
+            square,
+            lang.role(piece.role),
+            lang.side(piece.color)
+        ),
+        None => format!("{}: {}", square, lang.empty_square()),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fmt, iter::Peekable, str::FromStr, vec::IntoIter};
+
+use serde::{Serialize, Serializer};
+
+/// A boolean expression over puzzle tags, ex. `fork & !mateIn1`. `!` binds tighter than `&`,
+/// which binds tighter than `|`, and parentheses can be used for grouping.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagFilter {
+    Tag(String),
+    Not(Box<TagFilter>),
+    And(Box<TagFilter>, Box<TagFilter>),
+    Or(Box<TagFilter>, Box<TagFilter>),
+}
+
+impl TagFilter {
+    pub fn matches(&self, tags: &[String]) -> bool {
+        match self {
+            TagFilter::Tag(tag) => tags.contains(tag),
+            TagFilter::Not(filter) => !filter.matches(tags),
+            TagFilter::And(left, right) => left.matches(tags) && right.matches(tags),
+            TagFilter::Or(left, right) => left.matches(tags) || right.matches(tags),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Tag(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+type Tokens = Peekable<IntoIter<Token>>;
+
+impl FromStr for TagFilter {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<TagFilter, String> {
+        let mut tokens = tokenize(input)?.into_iter().peekable();
+        let filter = parse_or(&mut tokens)?;
+        match tokens.next() {
+            None => Ok(filter),
+            Some(_) => Err(format!("Unexpected input after '{}' in filter", filter)),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        let token = match c {
+            '&' => Token::And,
+            '|' => Token::Or,
+            '!' => Token::Not,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            c if c.is_alphanumeric() => {
+                let mut tag = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !c.is_alphanumeric() {
+                        break;
+                    }
+                    tag.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Tag(tag));
+                continue;
+            }
+            c => return Err(format!("Unexpected '{}' in filter", c)),
+        };
+        tokens.push(token);
+        chars.next();
+    }
+    Ok(tokens)
+}
+
+fn parse_or(tokens: &mut Tokens) -> Result<TagFilter, String> {
+    let mut filter = parse_and(tokens)?;
+    while tokens.peek() == Some(&Token::Or) {
+        tokens.next();
+        filter = TagFilter::Or(Box::new(filter), Box::new(parse_and(tokens)?));
+    }
+    Ok(filter)
+}
+
+fn parse_and(tokens: &mut Tokens) -> Result<TagFilter, String> {
+    let mut filter = parse_not(tokens)?;
+    while tokens.peek() == Some(&Token::And) {
+        tokens.next();
+        filter = TagFilter::And(Box::new(filter), Box::new(parse_not(tokens)?));
+    }
+    Ok(filter)
+}
+
+fn parse_not(tokens: &mut Tokens) -> Result<TagFilter, String> {
+    match tokens.next() {
+        Some(Token::Not) => Ok(TagFilter::Not(Box::new(parse_not(tokens)?))),
+        Some(Token::Open) => {
+            let filter = parse_or(tokens)?;
+            match tokens.next() {
+                Some(Token::Close) => Ok(filter),
+                _ => Err("Missing a closing parenthesis in filter".to_string()),
+            }
+        }
+        Some(Token::Tag(tag)) => Ok(TagFilter::Tag(tag)),
+        _ => Err("Expected a tag, '!' or '(' in filter".to_string()),
+    }
+}
+
+impl fmt::Display for TagFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagFilter::Tag(tag) => write!(f, "{}", tag),
+            TagFilter::Not(filter) => write!(f, "!{}", filter),
+            TagFilter::And(left, right) => write!(f, "({} & {})", left, right),
+            TagFilter::Or(left, right) => write!(f, "({} | {})", left, right),
+        }
+    }
+}
+
+/// Sent to the server as the expression string, for servers that can filter on it.
+impl Serialize for TagFilter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> String {
+        input.parse::<TagFilter>().unwrap().to_string()
+    }
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(parse("a | b & c"), "(a | (b & c))");
+        assert_eq!(parse("a & b | c"), "((a & b) | c)");
+        let filter: TagFilter = "fork | pin & endgame".parse().unwrap();
+        assert!(filter.matches(&tags(&["fork"])));
+        assert!(!filter.matches(&tags(&["pin"])));
+        assert!(filter.matches(&tags(&["pin", "endgame"])));
+    }
+
+    #[test]
+    fn not_binds_tightest() {
+        assert_eq!(parse("!a & b"), "(!a & b)");
+        assert_eq!(parse("!!a"), "!!a");
+        let filter: TagFilter = "fork & !mateIn1".parse().unwrap();
+        assert!(filter.matches(&tags(&["fork"])));
+        assert!(!filter.matches(&tags(&["fork", "mateIn1"])));
+    }
+
+    #[test]
+    fn parses_nested_parentheses() {
+        assert_eq!(
+            parse("((a | b) & (c | !(d & e)))"),
+            "((a | b) & (c | !(d & e)))"
+        );
+        assert_eq!(parse("!(a | b) & c"), "(!(a | b) & c)");
+        let filter: TagFilter = "(fork | pin) & !(mateIn1 | mateIn2)".parse().unwrap();
+        assert!(filter.matches(&tags(&["pin", "mateIn3"])));
+        assert!(!filter.matches(&tags(&["fork", "mateIn2"])));
+    }
+
+    #[test]
+    fn rejects_unbalanced_parentheses() {
+        assert!("(a & b".parse::<TagFilter>().is_err());
+        assert!("a & b)".parse::<TagFilter>().is_err());
+        assert!("((a)".parse::<TagFilter>().is_err());
+    }
+
+    #[test]
+    fn rejects_empty_expressions() {
+        assert!("".parse::<TagFilter>().is_err());
+        assert!("   ".parse::<TagFilter>().is_err());
+        assert!("()".parse::<TagFilter>().is_err());
+    }
+
+    #[test]
+    fn rejects_trailing_operators() {
+        assert!("a &".parse::<TagFilter>().is_err());
+        assert!("a |".parse::<TagFilter>().is_err());
+        assert!("a & !".parse::<TagFilter>().is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_characters() {
+        assert!("a && b".parse::<TagFilter>().is_err());
+        assert!("a, b".parse::<TagFilter>().is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use std::{
+    env,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use anyhow::Result;
+
+use crate::api::ChessTactic;
+use crate::i18n::Lang;
+use crate::screen::Screen;
+
+/// Something that happens during a run, which a hook can be set up to run on.
+#[derive(Clone, Copy, Debug)]
+pub enum Event {
+    PuzzleLoaded,
+    Solved,
+    Failed,
+    Skipped,
+}
+
+impl Event {
+    fn name(&self) -> &'static str {
+        match self {
+            Event::PuzzleLoaded => "puzzle_loaded",
+            Event::Solved => "solved",
+            Event::Failed => "failed",
+            Event::Skipped => "skipped",
+        }
+    }
+
+    /// The environment variable holding the hook's command, ex. `TACTICS_HOOK_SOLVED`.
+    fn variable(&self) -> String {
+        format!("TACTICS_HOOK_{}", self.name().to_uppercase())
+    }
+}
+
+/// Runs the shell command set for the event, if there is one, with the tactic as JSON on stdin
+/// and the event name in `TACTICS_EVENT`. A failing hook is reported but doesn't stop the run.
+pub fn fire(event: Event, tactic: &ChessTactic, lang: Lang, screen: &mut Screen) {
+    let command = match env::var(event.variable()) {
+        Ok(command) if !command.trim().is_empty() => command,
+        _ => return,
+    };
+    if let Err(err) = run(event, &command, tactic) {
+        screen.message(lang.hook_failed(event.name(), &err.to_string()));
+    }
+}
+
+fn run(event: Event, command: &str, tactic: &ChessTactic) -> Result<()> {
+    let mut child = shell(command)
+        .env("TACTICS_EVENT", event.name())
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(serde_json::to_string(tactic)?.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("{}", status);
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
@@ -0,0 +1,789 @@
+use std::env;
+
+use clap::ArgEnum;
+use shakmaty::{Color, Role, Square};
+
+/// The language used for everything printed to the user, chosen with `--lang` or detected from
+/// the `LANG` environment variable.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Lang {
+    En,
+    De,
+    Es,
+}
+
+const PIECE_ROLES: [Role; 5] = [
+    Role::Knight,
+    Role::Bishop,
+    Role::Rook,
+    Role::Queen,
+    Role::King,
+];
+
+impl Lang {
+    pub fn from_env() -> Lang {
+        env::var("LANG")
+            .map(|locale| Lang::from_locale(&locale))
+            .unwrap_or(Lang::En)
+    }
+
+    fn from_locale(locale: &str) -> Lang {
+        match locale.get(..2) {
+            Some("de") => Lang::De,
+            Some("es") => Lang::Es,
+            _ => Lang::En,
+        }
+    }
+
+    pub fn side(&self, color: Color) -> &'static str {
+        match (self, color) {
+            (Lang::En, Color::White) => "White",
+            (Lang::En, Color::Black) => "Black",
+            (Lang::De, Color::White) => "Weiß",
+            (Lang::De, Color::Black) => "Schwarz",
+            (Lang::Es, Color::White) => "Blancas",
+            (Lang::Es, Color::Black) => "Negras",
+        }
+    }
+
+    pub fn role(&self, role: Role) -> &'static str {
+        match (self, role) {
+            (Lang::En, Role::Pawn) => "pawn",
+            (Lang::En, Role::Knight) => "knight",
+            (Lang::En, Role::Bishop) => "bishop",
+            (Lang::En, Role::Rook) => "rook",
+            (Lang::En, Role::Queen) => "queen",
+            (Lang::En, Role::King) => "king",
+            (Lang::De, Role::Pawn) => "Bauer",
+            (Lang::De, Role::Knight) => "Springer",
+            (Lang::De, Role::Bishop) => "Läufer",
+            (Lang::De, Role::Rook) => "Turm",
+            (Lang::De, Role::Queen) => "Dame",
+            (Lang::De, Role::King) => "König",
+            (Lang::Es, Role::Pawn) => "peón",
+            (Lang::Es, Role::Knight) => "caballo",
+            (Lang::Es, Role::Bishop) => "alfil",
+            (Lang::Es, Role::Rook) => "torre",
+            (Lang::Es, Role::Queen) => "dama",
+            (Lang::Es, Role::King) => "rey",
+        }
+    }
+
+    /// The letter used for a piece in SAN, ex. `D` for the queen in German.
+    pub fn role_letter(&self, role: Role) -> char {
+        match (self, role) {
+            (Lang::En, role) => role.upper_char(),
+            (Lang::De, Role::Pawn) => 'B',
+            (Lang::De, Role::Knight) => 'S',
+            (Lang::De, Role::Bishop) => 'L',
+            (Lang::De, Role::Rook) => 'T',
+            (Lang::De, Role::Queen) => 'D',
+            (Lang::De, Role::King) => 'K',
+            (Lang::Es, Role::Pawn) => 'P',
+            (Lang::Es, Role::Knight) => 'C',
+            (Lang::Es, Role::Bishop) => 'A',
+            (Lang::Es, Role::Rook) => 'T',
+            (Lang::Es, Role::Queen) => 'D',
+            (Lang::Es, Role::King) => 'R',
+        }
+    }
+
+    /// Rewrites localized piece letters in a move to their English equivalents, so the move can
+    /// be compared against standard SAN. Letters that aren't used in this language are left
+    /// alone, so English input still works where it isn't ambiguous.
+    pub fn normalize_san(&self, input: &str) -> String {
+        input
+            .chars()
+            .map(|c| {
+                PIECE_ROLES
+                    .iter()
+                    .find(|role| self.role_letter(**role) == c)
+                    .map(|role| role.upper_char())
+                    .unwrap_or(c)
+            })
+            .collect()
+    }
+
+    pub fn prompt(&self, side: Color) -> String {
+        match self {
+            Lang::En => format!(
+                "{} to move, enter the best move, or '?' for help: ",
+                self.side(side)
+            ),
+            Lang::De => format!(
+                "{} am Zug, gib den besten Zug ein oder '?' für Hilfe: ",
+                self.side(side)
+            ),
+            Lang::Es => format!(
+                "Juegan {}, introduce la mejor jugada o '?' para ver la ayuda: ",
+                self.side(side)
+            ),
+        }
+    }
+
+    pub fn side_to_move(&self, side: Color) -> String {
+        match self {
+            Lang::En => format!("{} to move", self.side(side)),
+            Lang::De => format!("{} am Zug", self.side(side)),
+            Lang::Es => format!("Juegan {}", self.side(side)),
+        }
+    }
+
+    pub fn empty_square(&self) -> &'static str {
+        match self {
+            Lang::En => "empty",
+            Lang::De => "leer",
+            Lang::Es => "vacía",
+        }
+    }
+
+    pub fn tactic_rating(&self, rating: i32) -> String {
+        match self {
+            Lang::En => format!("This tactic is rated {}.", rating),
+            Lang::De => format!("Diese Aufgabe hat eine Wertung von {}.", rating),
+            Lang::Es => format!("Este ejercicio tiene una puntuación de {}.", rating),
+        }
+    }
+
+    pub fn incorrect_move(&self, input: &str) -> String {
+        match self {
+            Lang::En => format!("{} is not the correct move", input),
+            Lang::De => format!("{} ist nicht der richtige Zug", input),
+            Lang::Es => format!("{} no es la jugada correcta", input),
+        }
+    }
+
+    pub fn correct(&self) -> &'static str {
+        match self {
+            Lang::En => "Correct!",
+            Lang::De => "Richtig!",
+            Lang::Es => "¡Correcto!",
+        }
+    }
+
+    pub fn correct_move_was(&self, san: &str) -> String {
+        match self {
+            Lang::En => format!("The correct move was {}.", san),
+            Lang::De => format!("Der richtige Zug war {}.", san),
+            Lang::Es => format!("La jugada correcta era {}.", san),
+        }
+    }
+
+    pub fn responds_with(&self, side: Color, san: &str) -> String {
+        match self {
+            Lang::En => format!("{} responds with {}", self.side(side), san),
+            Lang::De => format!("{} antwortet mit {}", self.side(side), san),
+            Lang::Es => format!("{} responden con {}", self.side(side), san),
+        }
+    }
+
+    pub fn completed(&self) -> &'static str {
+        match self {
+            Lang::En => "Completed this tactic.",
+            Lang::De => "Aufgabe gelöst.",
+            Lang::Es => "Ejercicio completado.",
+        }
+    }
+
+    pub fn skipped(&self) -> &'static str {
+        match self {
+            Lang::En => "Skipped this tactic, fetching a new one.",
+            Lang::De => "Aufgabe übersprungen, eine neue wird geladen.",
+            Lang::Es => "Ejercicio saltado, cargando uno nuevo.",
+        }
+    }
+
+    /// The prompt's commands and what they do. In kids mode 'r' shows the streak, not a rating.
+    pub fn help_rows(&self, kids: bool) -> Vec<(&'static str, &'static str)> {
+        let rating = match (self, kids) {
+            (Lang::En, false) => "Show the rating of the current tactic.",
+            (Lang::En, true) => "Show how many good moves you've made in a row.",
+            (Lang::De, false) => "Zeige die Wertung der aktuellen Aufgabe.",
+            (Lang::De, true) => "Zeige, wie viele gute Züge du hintereinander gemacht hast.",
+            (Lang::Es, false) => "Muestra la puntuación del ejercicio actual.",
+            (Lang::Es, true) => "Muestra cuántas buenas jugadas llevas seguidas.",
+        };
+        match self {
+            Lang::En => vec![
+                (
+                    "Any move, ex. Qxd7 or ♕xd7",
+                    "Attempt to solve the tactic with the given move.",
+                ),
+                (
+                    "No input",
+                    "Reveal the answer, and continue the tactic if there are more moves.",
+                ),
+                (
+                    "'f' or 'fen'",
+                    "Print out the current board, in FEN notation.",
+                ),
+                ("'s' or 'show'", "Show the current board."),
+                ("'r' or 'rating'", rating),
+                ("'n' or 'skip'", "Skip this tactic and fetch a new one."),
+                (
+                    "'h' or 'hint'",
+                    "Show which piece to move. Hints lower the tactic's score.",
+                ),
+                ("'describe e4'", "Describe the piece on a square."),
+                (
+                    "'copy fen' or 'copy pgn'",
+                    "Copy the position, or the tactic's solution as PGN, to the clipboard.",
+                ),
+                ("'?' or 'help'", "Display this help."),
+            ],
+            Lang::De => vec![
+                (
+                    "Ein beliebiger Zug, z.B. Dxd7 oder ♕xd7",
+                    "Versuche, die Aufgabe mit diesem Zug zu lösen.",
+                ),
+                (
+                    "Keine Eingabe",
+                    "Zeige die Lösung und setze die Aufgabe fort, falls weitere Züge folgen.",
+                ),
+                (
+                    "'f' oder 'fen'",
+                    "Zeige die aktuelle Stellung in FEN-Notation.",
+                ),
+                ("'s' oder 'show'", "Zeige das aktuelle Brett."),
+                ("'r' oder 'rating'", rating),
+                (
+                    "'n' oder 'skip'",
+                    "Überspringe diese Aufgabe und lade eine neue.",
+                ),
+                (
+                    "'h' oder 'hint'",
+                    "Zeige, welche Figur ziehen soll. Hinweise senken die Punktzahl.",
+                ),
+                ("'describe e4'", "Beschreibe die Figur auf einem Feld."),
+                (
+                    "'copy fen' oder 'copy pgn'",
+                    "Kopiere die Stellung oder die Lösung als PGN in die Zwischenablage.",
+                ),
+                ("'?' oder 'help'", "Zeige diese Hilfe."),
+            ],
+            Lang::Es => vec![
+                (
+                    "Cualquier jugada, p. ej. Dxd7 o ♕xd7",
+                    "Intenta resolver el ejercicio con esa jugada.",
+                ),
+                (
+                    "Sin entrada",
+                    "Revela la respuesta y continúa si quedan más jugadas.",
+                ),
+                ("'f' o 'fen'", "Muestra la posición actual en notación FEN."),
+                ("'s' o 'show'", "Muestra el tablero actual."),
+                ("'r' o 'rating'", rating),
+                ("'n' o 'skip'", "Salta este ejercicio y carga uno nuevo."),
+                (
+                    "'h' o 'hint'",
+                    "Muestra qué pieza mover. Las pistas bajan la puntuación del ejercicio.",
+                ),
+                ("'describe e4'", "Describe la pieza de una casilla."),
+                (
+                    "'copy fen' o 'copy pgn'",
+                    "Copia la posición, o la solución en PGN, al portapapeles.",
+                ),
+                ("'?' o 'help'", "Muestra esta ayuda."),
+            ],
+        }
+    }
+
+    pub fn attackers_question(&self, side: Color, square: Square) -> String {
+        match self {
+            Lang::En => format!("How many {} pieces attack {}? ", self.side(side), square),
+            Lang::De => format!(
+                "Wie viele Figuren von {} greifen {} an? ",
+                self.side(side),
+                square
+            ),
+            Lang::Es => format!(
+                "¿Cuántas piezas {} atacan {}? ",
+                self.side(side).to_lowercase(),
+                square
+            ),
+        }
+    }
+
+    pub fn defended_question(&self, side: Color, role: Role, square: Square) -> String {
+        match self {
+            Lang::En => format!(
+                "Is the {} {} on {} defended? (y/n) ",
+                self.side(side).to_lowercase(),
+                self.role(role),
+                square
+            ),
+            Lang::De => format!(
+                "Ist die Figur auf {} ({}, {}) gedeckt? (j/n) ",
+                square,
+                self.side(side),
+                self.role(role)
+            ),
+            Lang::Es => format!(
+                "¿Está defendida la pieza en {} ({}, {})? (si/no) ",
+                square,
+                self.side(side).to_lowercase(),
+                self.role(role)
+            ),
+        }
+    }
+
+    pub fn yes_no(&self, answer: bool) -> &'static str {
+        match (self, answer) {
+            (Lang::En, true) => "yes",
+            (Lang::En, false) => "no",
+            (Lang::De, true) => "ja",
+            (Lang::De, false) => "nein",
+            (Lang::Es, true) => "sí",
+            (Lang::Es, false) => "no",
+        }
+    }
+
+    /// Parses a yes or no answer, accepting English answers in every language.
+    pub fn parse_yes_no(&self, reply: &str) -> Option<bool> {
+        match (self, reply) {
+            (_, "y") | (_, "yes") => Some(true),
+            (_, "n") | (_, "no") => Some(false),
+            (Lang::De, "j") | (Lang::De, "ja") => Some(true),
+            (Lang::De, "nein") => Some(false),
+            (Lang::Es, "si") | (Lang::Es, "sí") => Some(true),
+            _ => None,
+        }
+    }
+
+    pub fn answer_was(&self, answer: &str) -> String {
+        match self {
+            Lang::En => format!("The answer was {}.", answer),
+            Lang::De => format!("Die Antwort war {}.", answer),
+            Lang::Es => format!("La respuesta era {}.", answer),
+        }
+    }
+
+    pub fn wrong_answer(&self, reply: &str, answer: &str) -> String {
+        match self {
+            Lang::En => format!("{} is not correct, the answer was {}.", reply, answer),
+            Lang::De => format!("{} ist falsch, die Antwort war {}.", reply, answer),
+            Lang::Es => format!("{} no es correcto, la respuesta era {}.", reply, answer),
+        }
+    }
+
+    pub fn answered_correctly(&self, correct: usize, total: usize) -> String {
+        match self {
+            Lang::En => format!("Answered {} of {} questions correctly.", correct, total),
+            Lang::De => format!("{} von {} Fragen richtig beantwortet.", correct, total),
+            Lang::Es => format!("Has acertado {} de {} preguntas.", correct, total),
+        }
+    }
+
+    pub fn demo_move(&self, side: Color, san: &str, note: &str) -> String {
+        match self {
+            Lang::En => format!("{} plays {}. {}", self.side(side), san, note),
+            Lang::De => format!("{} spielt {}. {}", self.side(side), san, note),
+            Lang::Es => format!("{} juegan {}. {}", self.side(side), san, note),
+        }
+    }
+
+    pub fn press_enter(&self) -> &'static str {
+        match self {
+            Lang::En => "Press enter to start the drill.",
+            Lang::De => "Drücke Enter, um mit dem Training zu beginnen.",
+            Lang::Es => "Pulsa Intro para empezar el entrenamiento.",
+        }
+    }
+
+    pub fn no_warmup(&self, tag: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "There's no warmup for the {} theme yet, starting the drill.",
+                tag
+            ),
+            Lang::De => format!(
+                "Für das Thema {} gibt es noch kein Aufwärmen, das Training beginnt.",
+                tag
+            ),
+            Lang::Es => format!(
+                "Todavía no hay calentamiento para el tema {}, empieza el entrenamiento.",
+                tag
+            ),
+        }
+    }
+
+    pub fn warmup_needs_one_theme(&self) -> &'static str {
+        match self {
+            Lang::En => "Warmups need exactly one theme, ex. --tags fork.",
+            Lang::De => "Zum Aufwärmen wird genau ein Thema benötigt, z.B. --tags fork.",
+            Lang::Es => "El calentamiento necesita exactamente un tema, p. ej. --tags fork.",
+        }
+    }
+
+    pub fn theme_prompt(&self) -> &'static str {
+        match self {
+            Lang::En => "Which theme is this tactic? Enter its number or name: ",
+            Lang::De => "Welches Motiv hat diese Aufgabe? Gib die Nummer oder den Namen ein: ",
+            Lang::Es => "¿Qué tema tiene este ejercicio? Introduce su número o nombre: ",
+        }
+    }
+
+    pub fn themes_were(&self, themes: &str) -> String {
+        match self {
+            Lang::En => format!("This tactic's themes are {}.", themes),
+            Lang::De => format!("Die Motive dieser Aufgabe sind {}.", themes),
+            Lang::Es => format!("Los temas de este ejercicio son {}.", themes),
+        }
+    }
+
+    pub fn wrong_theme(&self, guess: &str, themes: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "{} isn't one of this tactic's themes, which are {}.",
+                guess, themes
+            ),
+            Lang::De => format!(
+                "{} gehört nicht zu den Motiven dieser Aufgabe, diese sind {}.",
+                guess, themes
+            ),
+            Lang::Es => format!(
+                "{} no es uno de los temas de este ejercicio, que son {}.",
+                guess, themes
+            ),
+        }
+    }
+
+    pub fn theme_summary(&self, theme_correct: bool, moves_correct: usize, moves: usize) -> String {
+        let theme = self.yes_no(theme_correct);
+        match self {
+            Lang::En => format!(
+                "Named the theme: {}. Found {} of {} moves.",
+                theme, moves_correct, moves
+            ),
+            Lang::De => format!(
+                "Motiv erkannt: {}. {} von {} Zügen gefunden.",
+                theme, moves_correct, moves
+            ),
+            Lang::Es => format!(
+                "Tema acertado: {}. {} de {} jugadas encontradas.",
+                theme, moves_correct, moves
+            ),
+        }
+    }
+
+    pub fn hint(&self, role: Role, from: Square) -> String {
+        match self {
+            Lang::En => format!("Try moving the {} on {}.", self.role(role), from),
+            Lang::De => format!("Ziehe mit der Figur auf {} ({}).", from, self.role(role)),
+            Lang::Es => format!("Prueba a mover la pieza de {} ({}).", from, self.role(role)),
+        }
+    }
+
+    pub fn hints_left(&self, hints: usize) -> String {
+        match self {
+            Lang::En => format!("{} hints left.", hints),
+            Lang::De => format!("Noch {} Hinweise übrig.", hints),
+            Lang::Es => format!("Quedan {} pistas.", hints),
+        }
+    }
+
+    pub fn no_hints_left(&self) -> &'static str {
+        match self {
+            Lang::En => "You've used all your hints.",
+            Lang::De => "Du hast alle Hinweise verbraucht.",
+            Lang::Es => "Has usado todas tus pistas.",
+        }
+    }
+
+    pub fn points(&self, points: usize, max: usize, hints: usize) -> String {
+        match self {
+            Lang::En => format!(
+                "Scored {} of {} points ({} hints used).",
+                points, max, hints
+            ),
+            Lang::De => format!(
+                "{} von {} Punkten erzielt ({} Hinweise benutzt).",
+                points, max, hints
+            ),
+            Lang::Es => format!("{} de {} puntos ({} pistas usadas).", points, max, hints),
+        }
+    }
+
+    pub fn destinations(&self, role: Role, from: Square, destinations: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "The {} on {} can move to {}.",
+                self.role(role),
+                from,
+                destinations
+            ),
+            Lang::De => format!(
+                "Die Figur auf {} ({}) kann nach {} ziehen.",
+                from,
+                self.role(role),
+                destinations
+            ),
+            Lang::Es => format!(
+                "La pieza de {} ({}) puede ir a {}.",
+                from,
+                self.role(role),
+                destinations
+            ),
+        }
+    }
+
+    pub fn destination_prompt(&self) -> &'static str {
+        match self {
+            Lang::En => "Move it to: ",
+            Lang::De => "Ziehen nach: ",
+            Lang::Es => "Mover a: ",
+        }
+    }
+
+    pub fn not_a_destination(&self, reply: &str) -> String {
+        match self {
+            Lang::En => format!("{} isn't one of those squares.", reply),
+            Lang::De => format!("{} ist keines dieser Felder.", reply),
+            Lang::Es => format!("{} no es una de esas casillas.", reply),
+        }
+    }
+
+    pub fn copied_fen(&self) -> &'static str {
+        match self {
+            Lang::En => "Copied the position's FEN to the clipboard.",
+            Lang::De => "Die FEN der Stellung wurde in die Zwischenablage kopiert.",
+            Lang::Es => "Se ha copiado el FEN de la posición al portapapeles.",
+        }
+    }
+
+    pub fn copied_pgn(&self) -> &'static str {
+        match self {
+            Lang::En => "Copied the solution's PGN to the clipboard.",
+            Lang::De => "Die PGN der Lösung wurde in die Zwischenablage kopiert.",
+            Lang::Es => "Se ha copiado el PGN de la solución al portapapeles.",
+        }
+    }
+
+    pub fn copy_failed(&self, err: &str) -> String {
+        match self {
+            Lang::En => format!("Couldn't copy to the clipboard: {}", err),
+            Lang::De => format!("Kopieren in die Zwischenablage fehlgeschlagen: {}", err),
+            Lang::Es => format!("No se pudo copiar al portapapeles: {}", err),
+        }
+    }
+
+    pub fn rate_limited(&self, seconds: u64) -> String {
+        match self {
+            Lang::En => format!(
+                "The server is rate limiting requests, trying again in {} seconds.",
+                seconds
+            ),
+            Lang::De => format!(
+                "Der Server begrenzt die Anfragen, neuer Versuch in {} Sekunden.",
+                seconds
+            ),
+            Lang::Es => format!(
+                "El servidor está limitando las peticiones, reintentando en {} segundos.",
+                seconds
+            ),
+        }
+    }
+
+    pub fn rate_limit_low(&self, remaining: u64) -> String {
+        match self {
+            Lang::En => format!(
+                "Only {} requests left before the server starts rate limiting.",
+                remaining
+            ),
+            Lang::De => format!(
+                "Nur noch {} Anfragen, bevor der Server Anfragen begrenzt.",
+                remaining
+            ),
+            Lang::Es => format!(
+                "Solo quedan {} peticiones antes de que el servidor empiece a limitarlas.",
+                remaining
+            ),
+        }
+    }
+
+    pub fn journal_prompt(&self) -> &'static str {
+        match self {
+            Lang::En => "What went wrong? Leave a note for your journal, or press enter to skip: ",
+            Lang::De => {
+                "Was ist schiefgelaufen? Hinterlasse eine Notiz für dein Journal, oder drücke \
+                 Enter zum Überspringen: "
+            }
+            Lang::Es => "¿Qué salió mal? Deja una nota en tu diario, o pulsa Intro para omitirla: ",
+        }
+    }
+
+    pub fn journal_empty(&self) -> &'static str {
+        match self {
+            Lang::En => "Your journal is empty. Run with --journal to add notes to it.",
+            Lang::De => "Dein Journal ist leer. Starte mit --journal, um Notizen hinzuzufügen.",
+            Lang::Es => "Tu diario está vacío. Usa --journal para añadir notas.",
+        }
+    }
+
+    pub fn no_journal_matches(&self, search: &str) -> String {
+        match self {
+            Lang::En => format!("No journal entries match \"{}\".", search),
+            Lang::De => format!("Keine Journaleinträge passen zu \"{}\".", search),
+            Lang::Es => format!("Ninguna entrada del diario coincide con \"{}\".", search),
+        }
+    }
+
+    pub fn journal_headers(&self) -> (&'static str, &'static str, &'static str, &'static str) {
+        match self {
+            Lang::En => ("Date", "Tactic", "Themes", "Note"),
+            Lang::De => ("Datum", "Aufgabe", "Motive", "Notiz"),
+            Lang::Es => ("Fecha", "Ejercicio", "Temas", "Nota"),
+        }
+    }
+
+    pub fn hook_failed(&self, event: &str, error: &str) -> String {
+        match self {
+            Lang::En => format!("The {} hook failed: {}", event, error),
+            Lang::De => format!("Der {}-Hook ist fehlgeschlagen: {}", event, error),
+            Lang::Es => format!("El hook {} ha fallado: {}", event, error),
+        }
+    }
+
+    pub fn kids_prompt(&self, side: Color) -> String {
+        match self {
+            Lang::En => format!("You're {}. What's the best move? ", self.side(side)),
+            Lang::De => format!("Du spielst {}. Was ist der beste Zug? ", self.side(side)),
+            Lang::Es => format!("Juegas con {}. ¿Cuál es la mejor jugada? ", self.side(side)),
+        }
+    }
+
+    /// Praise for a correct move, with a star for every correct move in a row.
+    pub fn kids_correct(&self, streak: usize) -> String {
+        let stars = "⭐".repeat(streak);
+        match self {
+            Lang::En => format!("✅ Great move! {}", stars),
+            Lang::De => format!("✅ Super Zug! {}", stars),
+            Lang::Es => format!("✅ ¡Muy bien! {}", stars),
+        }
+    }
+
+    pub fn kids_try_again(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ Not quite, try again!",
+            Lang::De => "❌ Nicht ganz, versuch es nochmal!",
+            Lang::Es => "❌ Casi, ¡inténtalo otra vez!",
+        }
+    }
+
+    pub fn kids_completed(&self) -> &'static str {
+        match self {
+            Lang::En => "🎉 Puzzle done!",
+            Lang::De => "🎉 Aufgabe geschafft!",
+            Lang::Es => "🎉 ¡Ejercicio terminado!",
+        }
+    }
+
+    pub fn kids_streak(&self, streak: usize) -> String {
+        match self {
+            Lang::En => format!("⭐ {} good moves in a row", streak),
+            Lang::De => format!("⭐ {} gute Züge hintereinander", streak),
+            Lang::Es => format!("⭐ {} buenas jugadas seguidas", streak),
+        }
+    }
+
+    pub fn analysis_offer(&self) -> &'static str {
+        match self {
+            Lang::En => "Explore the final position on an analysis board? (y/N) ",
+            Lang::De => "Die Endstellung auf einem Analysebrett untersuchen? (j/N) ",
+            Lang::Es => "¿Explorar la posición final en un tablero de análisis? (sí/N) ",
+        }
+    }
+
+    pub fn analysis_started(&self) -> &'static str {
+        match self {
+            Lang::En => "Play moves for either side. 'u' takes a move back, 'q' quits.",
+            Lang::De => "Spiele Züge für beide Seiten. 'u' nimmt einen Zug zurück, 'q' beendet.",
+            Lang::Es => "Juega por ambos bandos. 'u' deshace una jugada, 'q' sale.",
+        }
+    }
+
+    pub fn analysis_prompt(&self, side: Color) -> String {
+        match self {
+            Lang::En => format!("{} to move, enter any move, 'u' or 'q': ", self.side(side)),
+            Lang::De => format!(
+                "{} am Zug, gib einen Zug, 'u' oder 'q' ein: ",
+                self.side(side)
+            ),
+            Lang::Es => format!(
+                "Juegan {}, escribe una jugada, 'u' o 'q': ",
+                self.side(side)
+            ),
+        }
+    }
+
+    pub fn analysis_move(&self, side: Color, san: &str) -> String {
+        match self {
+            Lang::En => format!("{} played {}", self.side(side), san),
+            Lang::De => format!("{} spielte {}", self.side(side), san),
+            Lang::Es => format!("{} jugaron {}", self.side(side), san),
+        }
+    }
+
+    pub fn illegal_move(&self, input: &str) -> String {
+        match self {
+            Lang::En => format!("{} isn't a legal move here", input),
+            Lang::De => format!("{} ist hier kein legaler Zug", input),
+            Lang::Es => format!("{} no es una jugada legal aquí", input),
+        }
+    }
+
+    pub fn nothing_to_undo(&self) -> &'static str {
+        match self {
+            Lang::En => "You're back at the final position of the tactic.",
+            Lang::De => "Du bist wieder bei der Endstellung der Aufgabe.",
+            Lang::Es => "Has vuelto a la posición final del ejercicio.",
+        }
+    }
+
+    pub fn broken_tactic(&self, id: &str, reason: &str) -> String {
+        match self {
+            Lang::En => format!("Skipping tactic {}, {}", id, reason),
+            Lang::De => format!("Aufgabe {} wird übersprungen: {}", id, reason),
+            Lang::Es => format!("Se omite el ejercicio {}: {}", id, reason),
+        }
+    }
+
+    pub fn game_context(&self, moves: &str) -> String {
+        match self {
+            Lang::En => format!("How the game got here: {}", moves),
+            Lang::De => format!("So kam es in der Partie dazu: {}", moves),
+            Lang::Es => format!("Así se llegó a esta posición en la partida: {}", moves),
+        }
+    }
+
+    pub fn no_game_context(&self, error: &str) -> String {
+        match self {
+            Lang::En => format!("Couldn't get the moves of the game ({})", error),
+            Lang::De => format!(
+                "Die Züge der Partie konnten nicht geladen werden ({})",
+                error
+            ),
+            Lang::Es => format!(
+                "No se pudieron obtener las jugadas de la partida ({})",
+                error
+            ),
+        }
+    }
+
+    pub fn server_version_mismatch(&self, server: &str, client: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "The server speaks version {} of the API, but this client expects version {}.",
+                server, client
+            ),
+            Lang::De => format!(
+                "Der Server spricht Version {} der API, dieser Client erwartet Version {}.",
+                server, client
+            ),
+            Lang::Es => format!(
+                "El servidor usa la versión {} de la API, pero este cliente espera la versión {}.",
+                server, client
+            ),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use prettytable::Table;
+use serde::{Deserialize, Serialize};
+
+use crate::api::ChessTactic;
+use crate::i18n::Lang;
+use crate::screen::Screen;
+use crate::{motifs, storage};
+
+const JOURNAL_FILE: &str = "journal.jsonl";
+
+/// A failed attempt at a tactic, with the user's note on what went wrong. The journal is stored
+/// as one JSON entry per line.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    timestamp: u64,
+    tactic_id: String,
+    fen: String,
+    moves: Vec<String>,
+    tags: Vec<String>,
+    rating: i32,
+    moves_correct: usize,
+    moves_total: usize,
+    note: String,
+}
+
+impl Entry {
+    fn matches(&self, search: &str) -> bool {
+        self.note.to_lowercase().contains(search)
+            || self
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(search))
+    }
+}
+
+fn journal_path() -> Result<PathBuf> {
+    Ok(storage::data_dir()?.join(JOURNAL_FILE))
+}
+
+/// Asks what went wrong in a failed tactic, and saves the answer unless it's left empty.
+pub fn prompt_note(
+    tactic: &ChessTactic,
+    moves_correct: usize,
+    moves_total: usize,
+    lang: Lang,
+    screen: &mut Screen,
+) -> Result<()> {
+    screen.gap();
+    let note = screen.prompt(lang.journal_prompt())?;
+    let note = note.trim();
+    if note.is_empty() {
+        return Ok(());
+    }
+    let entry = Entry {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        tactic_id: tactic.id.clone(),
+        fen: tactic.fen.clone(),
+        moves: tactic.moves.clone(),
+        tags: tactic.tags.clone(),
+        rating: tactic.rating,
+        moves_correct,
+        moves_total,
+        note: note.to_string(),
+    };
+    let path = journal_path()?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Couldn't open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+    Ok(())
+}
+
+fn read_entries() -> Result<Vec<Entry>> {
+    let path = journal_path()?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    fs::read_to_string(&path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Couldn't read a journal entry in {}", path.display()))
+        })
+        .collect()
+}
+
+/// Prints the journal, newest entries first, optionally only those whose note or tags contain
+/// the search text.
+pub fn show(search: Option<&str>, lang: Lang) -> Result<()> {
+    let entries = read_entries()?;
+    let search = search.map(|search| search.to_lowercase());
+    let matches = entries
+        .iter()
+        .rev()
+        .filter(|entry| search.as_ref().is_none_or(|search| entry.matches(search)))
+        .collect::<Vec<&Entry>>();
+    if matches.is_empty() {
+        match &search {
+            Some(search) => println!("{}", lang.no_journal_matches(search)),
+            None => println!("{}", lang.journal_empty()),
+        }
+        return Ok(());
+    }
+    let (date, tactic, themes, note) = lang.journal_headers();
+    let mut table = Table::new();
+    table.set_titles(row![date, tactic, themes, note]);
+    for entry in matches {
+        table.add_row(row![
+            format_date(entry.timestamp),
+            format!("{} ({})", entry.tactic_id, entry.rating),
+            motifs::themes(&entry.tags).join(", "),
+            entry.note
+        ]);
+    }
+    table.printstd();
+    Ok(())
+}
+
+/// Formats a unix timestamp as a UTC date, using Howard Hinnant's days-to-civil algorithm.
+fn format_date(timestamp: u64) -> String {
+    let z = (timestamp / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -1,13 +1,14 @@
-use std::env;
 #[macro_use]
 extern crate anyhow;
 #[macro_use]
 extern crate prettytable;
 
+use std::time::Duration;
+
 use anyhow::Context;
-use clap::{AppSettings, Parser};
+use clap::{AppSettings, ArgEnum, Parser, Subcommand};
 use colored::*;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use shakmaty::{
     fen::{self, Fen},
     san::{self, San},
@@ -16,6 +17,37 @@ use shakmaty::{
 };
 
 use anyhow::Result;
+use api::{ChessTactic, ChessTacticRequest};
+use filter::TagFilter;
+use i18n::Lang;
+use notation::Notation;
+use prettytable::Table;
+use rng::Rng;
+use screen::{Layout, Screen};
+use seen::SeenPuzzles;
+use theme::{BoardStyle, Theme};
+
+mod analysis;
+mod api;
+mod clipboard;
+mod context;
+mod demo;
+mod describe;
+mod filter;
+mod hooks;
+mod i18n;
+mod journal;
+#[cfg(test)]
+mod mock_server;
+mod motifs;
+mod notation;
+mod rng;
+mod screen;
+mod seen;
+mod storage;
+mod theme;
+mod tutorial;
+mod vision;
 
 #[derive(Parser, Debug)]
 #[clap(version = "1.0", author = "Marcus B. <me@mbuffett.com>")]
@@ -27,14 +59,183 @@ struct Args {
     rating: Option<String>,
     #[clap(short, long)]
     /// Optionally specify a list of tags to get tactics for. Every tactic returned will have one
-    /// of these tags
+    /// of these tags, or all of them with --all-tags
     tags: Vec<String>,
+    #[clap(long)]
+    /// Only return tactics that have every one of the given tags
+    all_tags: bool,
+    #[clap(long)]
+    /// A tag filter expression, ex. "fork & !mateIn1". Tags can be combined with & (and), | (or),
+    /// ! (not) and parentheses
+    filter: Option<TagFilter>,
+    #[clap(long)]
+    /// Only return forced mates in this many moves, from 1 to 5. The solution is checked to be
+    /// exactly that long and to end in mate
+    mate_in: Option<usize>,
+    #[clap(long)]
+    /// Tags to avoid. No tactic returned will have any of these tags
+    exclude_tags: Vec<String>,
+    #[clap(long)]
+    /// Skip tactics whose rating deviation is above this, since their rating isn't reliable yet
+    max_deviation: Option<i32>,
+    #[clap(long)]
+    /// Only return tactics with at least this popularity. Popularity goes from -100 to 100,
+    /// based on how players voted on the puzzle
+    min_popularity: Option<i32>,
+    #[clap(long)]
+    /// Only return tactics that have been played at least this many times
+    min_plays: Option<i32>,
+    #[clap(long, arg_enum, default_value = "standard")]
+    /// The variant to fetch tactics for. Chess960 puzzles may start from positions where castling
+    /// rights don't follow the standard king and rook squares.
+    variant: Variant,
+    #[clap(long, arg_enum)]
+    /// The language to use. Defaults to the language in the LANG environment variable, or English
+    /// if it isn't supported. Moves can be entered with this language's piece letters.
+    lang: Option<Lang>,
+    #[clap(long, arg_enum)]
+    /// How to write moves in the output: with figurines, or English, German or Spanish piece
+    /// letters. Defaults to the letters of the language in use
+    notation: Option<Notation>,
+    #[clap(long)]
+    /// When a single tag is given, explain the motif and play through a worked example before
+    /// starting.
+    warmup: bool,
+    #[clap(long)]
+    /// Keep the board at the top of the terminal and redraw it in place after every move,
+    /// instead of printing a new board when asked.
+    pin_board: bool,
+    #[clap(long, arg_enum, default_value = "auto")]
+    /// The colors to draw boards with, for a dark or light terminal background. Auto goes by the
+    /// COLORFGBG environment variable, if the terminal sets it
+    theme: Theme,
+    #[clap(long)]
+    /// Describe positions in words instead of drawing the board, for use with a screen reader
+    accessible: bool,
+    #[clap(long)]
+    /// Before each tactic, name its theme (fork, pin, deflection, ...) before finding the moves.
+    /// The theme and the moves are scored separately
+    name_the_theme: bool,
+    #[clap(long)]
+    /// How many hints can be used over this run. Each hint lowers the score of the tactic it's
+    /// used on. Hints are unlimited if this isn't given
+    hints: Option<usize>,
+    #[clap(long)]
+    /// Enter moves as squares: first the square of the piece to move, which shows where it can
+    /// go, then its destination. SAN is still accepted
+    square_input: bool,
+    #[clap(long, conflicts_with = "rating")]
+    /// A mode for young players: easy tactics only, simpler messages with emoji, a streak
+    /// counter, figurine pieces and no rating numbers
+    kids: bool,
+    #[clap(long)]
+    /// Before each standard tactic, show the last few moves of the Lichess game it comes from
+    game_context: bool,
+    #[clap(long)]
+    /// After a tactic you didn't fully solve, ask for a note on what went wrong and save it to
+    /// your journal
+    journal: bool,
+    #[clap(long)]
+    /// After a tactic, offer to explore its final position on an analysis board
+    analysis: bool,
+    #[clap(long)]
+    /// Never show a tactic that was shown in the last this many runs. The IDs of recent tactics
+    /// are kept on disk
+    shuffle_window: Option<usize>,
+    #[clap(long)]
+    /// A proxy to connect to the server through, ex. http://proxy.example.com:8080. Without it,
+    /// the HTTPS_PROXY and HTTP_PROXY environment variables are used. Networks with their own
+    /// certificate authority can set TACTICS_CA_BUNDLE to a PEM file of its certificates
+    proxy: Option<String>,
+    #[clap(long)]
+    /// Tell the server about tactics whose position or moves are broken, which are always
+    /// skipped
+    report_broken: bool,
+    #[clap(long)]
+    /// Make random choices from this seed, so that runs with the same seed and options go the
+    /// same way. Tactics are only picked the same way by servers that support seeding
+    seed: Option<u64>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Train board vision by answering questions about the attackers and defenders in a position
+    Vision {
+        #[clap(short, long, default_value = "5")]
+        /// How many questions to ask about the position
+        questions: usize,
+    },
+    /// Learn how to use the trainer, with a couple of beginner tactics
+    Tutorial,
+    /// Play through tactics automatically, typing out the solutions, ex. for screencasts
+    Demo {
+        #[clap(short, long, default_value = "3")]
+        /// How many tactics to play through
+        count: usize,
+        #[clap(short, long, default_value = "1000")]
+        /// The pause before each move, in milliseconds
+        delay: u64,
+    },
+    /// Browse the notes saved with --journal, newest first
+    Journal {
+        #[clap(short, long)]
+        /// Only show entries whose note or tags contain this text
+        search: Option<String>,
+    },
+}
+
+#[derive(ArgEnum, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    Standard,
+    Chess960,
+}
+
+impl Variant {
+    pub fn is_standard(&self) -> bool {
+        *self == Variant::Standard
+    }
+
+    fn castling_mode(&self) -> CastlingMode {
+        match self {
+            Variant::Standard => CastlingMode::Standard,
+            Variant::Chess960 => CastlingMode::Chess960,
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let opts = Args::parse();
     // dbg!(&opts);
+    let lang = opts.lang.unwrap_or_else(Lang::from_env);
+    let layout = if opts.accessible {
+        Layout::Accessible
+    } else if opts.pin_board {
+        Layout::Pinned
+    } else {
+        Layout::Scrolling
+    };
+    let style = BoardStyle {
+        figurines: opts.kids,
+        palette: opts.theme.palette(),
+    };
+    let notation = opts.notation.unwrap_or_else(|| Notation::for_lang(lang));
+    let mut screen = Screen::new(layout, lang, style, notation);
+    let mut solve_options = SolveOptions {
+        name_the_theme: opts.name_the_theme,
+        hints_left: opts.hints,
+        square_input: opts.square_input,
+        variant: opts.variant,
+        streak: if opts.kids { Some(0) } else { None },
+    };
+    match &opts.command {
+        Some(Command::Tutorial) => return tutorial::run(lang, &mut screen),
+        Some(Command::Journal { search }) => return journal::show(search.as_deref(), lang),
+        _ => {}
+    }
     let (rating_lower_bound, rating_upper_bound): (Option<i32>, Option<i32>) = {
         match opts.rating {
             Some(rating) => {
@@ -53,108 +254,494 @@ async fn main() -> Result<()> {
                 };
                 (Some(0), Some(0))
             }
+            None if opts.kids => (None, Some(KIDS_MAX_RATING)),
             None => (None, None),
         }
     };
-    let tactic = get_new_puzzle(ChessTacticRequest {
+    if opts.warmup {
+        match opts.tags.as_slice() {
+            [tag] => match motifs::find(tag) {
+                Some(motif) => motifs::warmup(motif, lang, &mut screen)?,
+                None => screen.message(lang.no_warmup(tag)),
+            },
+            _ => screen.message(lang.warmup_needs_one_theme().to_string()),
+        }
+    }
+    let filter = match opts.mate_in {
+        Some(moves) => {
+            if !(1..=MAX_MATE_IN).contains(&moves) {
+                bail!("--mate-in must be between 1 and {}", MAX_MATE_IN);
+            }
+            let mate = TagFilter::Tag(format!("mateIn{}", moves));
+            Some(match opts.filter {
+                Some(filter) => TagFilter::And(Box::new(filter), Box::new(mate)),
+                None => mate,
+            })
+        }
+        None => opts.filter,
+    };
+    let request = ChessTacticRequest {
         rating_gte: rating_lower_bound,
         rating_lte: rating_upper_bound,
         tags: opts.tags,
-    })
-    .await
-    .expect("Failed to get a new tactic from the server, exiting.");
-    let fen = tactic.fen;
+        all_tags: opts.all_tags,
+        filter,
+        mate_in: opts.mate_in,
+        exclude_tags: opts.exclude_tags,
+        rating_deviation_lte: opts.max_deviation,
+        popularity_gte: opts.min_popularity,
+        number_plays_gte: opts.min_plays,
+        variant: opts.variant,
+    };
+    let mut rng = Rng::new(opts.seed);
+    let mut client = api::Client::new(api::server_url(), lang, opts.proxy.as_deref(), opts.seed)?;
+    if let Ok(dir) = storage::data_dir() {
+        client.set_cache_dir(dir);
+    }
+    client.discover(&mut screen).await;
+    let mut seen = match opts.shuffle_window {
+        Some(window) => Some(SeenPuzzles::load(window)?),
+        None => None,
+    };
+    if let Some(Command::Demo { count, delay }) = &opts.command {
+        client.set_batch_size(*count);
+        for _ in 0..*count {
+            let tactic = get_matching_puzzle(
+                &client,
+                &request,
+                seen.as_ref(),
+                opts.report_broken,
+                lang,
+                &mut screen,
+            )
+            .await?;
+            let position = puzzle_position(&tactic, opts.variant)?;
+            screen.show_board(&position);
+            demo::play(
+                &tactic,
+                position,
+                lang,
+                &mut screen,
+                Duration::from_millis(*delay),
+            )?;
+        }
+        return Ok(());
+    }
+    loop {
+        let tactic = get_matching_puzzle(
+            &client,
+            &request,
+            seen.as_ref(),
+            opts.report_broken,
+            lang,
+            &mut screen,
+        )
+        .await?;
+        let position = puzzle_position(&tactic, opts.variant)?;
+        if let Some(Command::Vision { questions }) = &opts.command {
+            screen.show_board(&position);
+            return vision::drill(&position, *questions, lang, &mut screen, &mut rng);
+        }
+        if let Some(seen) = &mut seen {
+            seen.record(&tactic.id)?;
+        }
+        hooks::fire(hooks::Event::PuzzleLoaded, &tactic, lang, &mut screen);
+        if opts.game_context && opts.variant == Variant::Standard {
+            match context::lead_up(&client, &tactic).await {
+                Ok(Some(moves)) => screen.message(lang.game_context(&screen.san(&moves))),
+                Ok(None) => {}
+                Err(err) => screen.message(lang.no_game_context(&err.to_string())),
+            }
+        }
+        screen.show_board(&position);
+        match solve(&tactic, position, lang, &mut screen, &mut solve_options)? {
+            Outcome::Completed(score, position) => {
+                let event = if score.moves_correct == score.moves_total {
+                    hooks::Event::Solved
+                } else {
+                    hooks::Event::Failed
+                };
+                hooks::fire(event, &tactic, lang, &mut screen);
+                if opts.hints.is_some() || score.hints_used > 0 {
+                    screen.message(lang.points(score.points(), MAX_POINTS, score.hints_used));
+                }
+                if let Some(theme_correct) = score.theme_correct {
+                    screen.message(lang.theme_summary(
+                        theme_correct,
+                        score.moves_correct,
+                        score.moves_total,
+                    ));
+                }
+                if opts.journal && score.moves_correct < score.moves_total {
+                    journal::prompt_note(
+                        &tactic,
+                        score.moves_correct,
+                        score.moves_total,
+                        lang,
+                        &mut screen,
+                    )?;
+                }
+                if opts.analysis {
+                    analysis::offer(position, lang, &mut screen)?;
+                }
+                return Ok(());
+            }
+            Outcome::Skipped => {
+                hooks::fire(hooks::Event::Skipped, &tactic, lang, &mut screen);
+                screen.message(lang.skipped().to_string());
+            }
+        }
+    }
+}
+
+/// Sets up the tactic's FEN and plays the opponent's move which leads into the tactic, unless
+/// the solver moves first.
+fn puzzle_position(tactic: &ChessTactic, variant: Variant) -> Result<Chess> {
+    let setup: Fen = tactic.fen.parse()?;
     // let fen = "r6k/pp2r2p/4Rp1Q/3p4/8/1N1P2R1/PqP2bPP/7K b - - 0 24";
-    let moves = tactic.moves;
-    let setup: Fen = fen.parse()?;
-    let mut position: Chess = setup.position(CastlingMode::Standard)?;
-    let mut continuation_moves = moves.iter().map(|m| -> Uci { m.parse().unwrap() });
-    let first_move = &continuation_moves
-        .next()
-        .unwrap()
-        .to_move(&position)
-        .unwrap();
-    let their_side = position.turn();
-    position = position.play(first_move).unwrap();
-    println!();
-    print_board(&position);
-    let mut next_move = continuation_moves
-        .next()
-        .unwrap()
-        .to_move(&position)
-        .unwrap();
+    let mut position: Chess = setup.position(variant.castling_mode())?;
+    for m in tactic.moves.iter().take(tactic.setup_moves()) {
+        let m = m.parse::<Uci>()?.to_move(&position)?;
+        position = position.play(&m)?;
+    }
+    Ok(position)
+}
+
+/// The hardest tactics fetched in kids mode.
+const KIDS_MAX_RATING: i32 = 1000;
+
+struct SolveOptions {
+    name_the_theme: bool,
+    /// Hints left for the rest of this run, or `None` if they're unlimited.
+    hints_left: Option<usize>,
+    square_input: bool,
+    variant: Variant,
+    /// Correct moves in a row, counted in kids mode. Kids mode also swaps in simpler messages
+    /// and hides ratings.
+    streak: Option<usize>,
+}
+
+const MAX_POINTS: usize = 100;
+const HINT_PENALTY: usize = 25;
+
+struct Score {
+    moves_correct: usize,
+    moves_total: usize,
+    hints_used: usize,
+    /// Whether the theme was named correctly, if it was asked for.
+    theme_correct: Option<bool>,
+}
+
+impl Score {
+    /// The share of moves found, out of `MAX_POINTS`, less a penalty for every hint used.
+    fn points(&self) -> usize {
+        let found = MAX_POINTS * self.moves_correct / self.moves_total.max(1);
+        found.saturating_sub(HINT_PENALTY * self.hints_used)
+    }
+}
+
+enum Outcome {
+    /// The tactic was played to the end, leaving the final position.
+    Completed(Score, Chess),
+    Skipped,
+}
+
+fn solve(
+    tactic: &ChessTactic,
+    mut position: Chess,
+    lang: Lang,
+    screen: &mut Screen,
+    options: &mut SolveOptions,
+) -> Result<Outcome> {
+    let mut continuation_moves = tactic.moves.iter().skip(tactic.setup_moves());
+    let their_side = !position.turn();
+    let mut next_move = line_move(continuation_moves.next(), &position)?;
+    let mut score = Score {
+        moves_correct: 0,
+        moves_total: 0,
+        hints_used: 0,
+        theme_correct: None,
+    };
+    if options.name_the_theme {
+        score.theme_correct = guess_theme(tactic, lang, screen)?;
+    }
     loop {
-        println!();
+        screen.gap();
         let san_move = San::from_move(&position, &next_move);
         // dbg!(&san_move.to_string());
-        let reply = get_prompt_response(&position);
-        println!();
+        let reply = get_prompt_response(&position, lang, screen, options.streak.is_some())?;
+        screen.gap();
         let mut correct = false;
         match reply {
             PromptResponse::ShowBoard => {
-                print_board(&position);
+                screen.show_board(&position);
                 continue;
             }
             PromptResponse::Help => {
-                print_help();
+                print_help(lang, options.streak.is_some(), screen);
                 continue;
             }
             PromptResponse::PrintFen => {
-                println!("{}", fen::epd(&position).to_string());
+                screen.message(fen::epd(&position).to_string());
                 continue;
             }
             PromptResponse::NoResponse => {}
             PromptResponse::ShowRating => {
-                println!("This tactic is rated {}.", tactic.rating);
+                match options.streak {
+                    Some(streak) => screen.message(lang.kids_streak(streak)),
+                    None => screen.message(lang.tactic_rating(tactic.rating)),
+                }
+                continue;
+            }
+            PromptResponse::Describe(square) => {
+                screen.message(describe::describe_square(&position, square, lang));
                 continue;
             }
+            PromptResponse::Hint => {
+                if options.hints_left == Some(0) {
+                    screen.message(lang.no_hints_left().to_string());
+                    continue;
+                }
+                if let Some(hints_left) = options.hints_left.as_mut() {
+                    *hints_left -= 1;
+                }
+                score.hints_used += 1;
+                if let Some(from) = next_move.from() {
+                    screen.message(lang.hint(next_move.role(), from));
+                }
+                if let Some(hints_left) = options.hints_left {
+                    screen.message(lang.hints_left(hints_left));
+                }
+                continue;
+            }
+            PromptResponse::CopyFen => {
+                match screen.copy(fen::fen(&position)) {
+                    Ok(()) => screen.message(lang.copied_fen().to_string()),
+                    Err(err) => screen.message(lang.copy_failed(&err.to_string())),
+                }
+                continue;
+            }
+            PromptResponse::CopyPgn => {
+                match solution_pgn(tactic, options.variant).and_then(|pgn| screen.copy(pgn)) {
+                    Ok(()) => screen.message(lang.copied_pgn().to_string()),
+                    Err(err) => screen.message(lang.copy_failed(&err.to_string())),
+                }
+                continue;
+            }
+            PromptResponse::Skip => return Ok(Outcome::Skipped),
             PromptResponse::Move(move_input) => {
-                if move_input == san_move.to_string() {
+                let (attempt, attempt_name) = match move_input.parse::<Square>() {
+                    // A square without a piece that can move, like e4, is read as a move instead
+                    Ok(from) if options.square_input && can_move_from(&position, from) => {
+                        match pick_destination(from, &position, lang, screen)? {
+                            Some(m) => {
+                                let name = screen.san(&San::from_move(&position, &m).to_string());
+                                (Some(m), name)
+                            }
+                            None => continue,
+                        }
+                    }
+                    _ => (
+                        notation::parse_move(&move_input, &position, lang),
+                        move_input,
+                    ),
+                };
+                if attempt.as_ref() == Some(&next_move) {
                     correct = true;
+                    score.moves_correct += 1;
+                    if let Some(streak) = &mut options.streak {
+                        *streak += 1;
+                    }
                 } else {
-                    println!("{} is not the correct move", move_input);
+                    match &mut options.streak {
+                        Some(streak) => {
+                            *streak = 0;
+                            screen.message(lang.kids_try_again().to_string());
+                        }
+                        None => screen.message(lang.incorrect_move(&attempt_name)),
+                    }
                     continue;
                 }
             }
         }
-        let reply = san_move.to_move(&position).unwrap();
+        score.moves_total += 1;
+        if !correct {
+            if let Some(streak) = &mut options.streak {
+                *streak = 0;
+            }
+        }
+        let reply = san_move.to_move(&position)?;
         let old_position = position.clone();
-        position = position.play(&reply).unwrap();
+        position = position.play(&reply)?;
+        if !correct {
+            screen.show_move(&old_position, &position, &reply);
+        }
         let response = continuation_moves.next();
         match response {
             Some(response) => {
                 let prefix = if correct {
-                    "Correct! ".to_string()
+                    match options.streak {
+                        Some(streak) => lang.kids_correct(streak),
+                        None => lang.correct().to_string(),
+                    }
                 } else {
-                    format!("The correct move was {}. ", san_move.to_string())
+                    lang.correct_move_was(&screen.san(&san_move.to_string()))
                 };
-                let response = response.to_move(&position).unwrap();
-                let response_san = San::from_move(&position, &response);
-                println!(
-                    "{}{} responds with {}",
+                let response = line_move(Some(response), &position)?;
+                let response_san = screen.san(&San::from_move(&position, &response).to_string());
+                screen.message(format!(
+                    "{} {}",
                     prefix,
-                    print_side(&their_side),
-                    response_san.to_string()
-                );
-                position = position.play(&response).unwrap();
-                next_move = continuation_moves
-                    .next()
-                    .unwrap()
-                    .to_move(&position)
-                    .unwrap();
+                    lang.responds_with(their_side, &response_san)
+                ));
+                position = position.play(&response)?;
+                screen.update_board(&position);
+                next_move = line_move(continuation_moves.next(), &position)?;
             }
             None => {
-                let prefix = if correct {
-                    "Correct! ".to_string()
-                } else {
-                    "".to_string()
+                screen.update_board(&position);
+                let message = match (options.streak, correct) {
+                    (Some(streak), true) => {
+                        format!("{} {}", lang.kids_correct(streak), lang.kids_completed())
+                    }
+                    (Some(_), false) => lang.kids_completed().to_string(),
+                    (None, true) => format!("{} {}", lang.correct(), lang.completed()),
+                    (None, false) => lang.completed().to_string(),
                 };
-                println!("{}Completed this tactic.", prefix);
+                screen.message(message);
                 break;
             }
         };
     }
-    return Ok(());
+    return Ok(Outcome::Completed(score, position));
+}
+
+/// The next move of the tactic's line as a legal move in the position. Tactics are validated
+/// before they're solved, so this only fails for lines that end early or have illegal moves.
+fn line_move(uci: Option<&String>, position: &Chess) -> Result<Move> {
+    let uci = uci.ok_or_else(|| anyhow!("The tactic's line ends before the solver's last move"))?;
+    Ok(uci.parse::<Uci>()?.to_move(position)?)
+}
+
+/// The tactic's whole line as PGN, starting from its FEN, so it can be pasted into an analysis
+/// board.
+fn solution_pgn(tactic: &ChessTactic, variant: Variant) -> Result<String> {
+    let setup: Fen = tactic.fen.parse()?;
+    let mut position: Chess = setup.position(variant.castling_mode())?;
+    let mut number = tactic
+        .fen
+        .split_whitespace()
+        .nth(5)
+        .and_then(|number| number.parse::<u32>().ok())
+        .unwrap_or(1);
+    let mut movetext = vec![];
+    for (i, uci) in tactic.moves.iter().enumerate() {
+        let m = uci.parse::<Uci>()?.to_move(&position)?;
+        let san = San::from_move(&position, &m);
+        match position.turn() {
+            Color::White => movetext.push(format!("{}. {}", number, san)),
+            Color::Black if i == 0 => movetext.push(format!("{}... {}", number, san)),
+            Color::Black => movetext.push(san.to_string()),
+        }
+        if position.turn() == Color::Black {
+            number += 1;
+        }
+        position = position.play(&m)?;
+    }
+    let mut headers = format!("[FEN \"{}\"]\n[SetUp \"1\"]\n", tactic.fen);
+    if variant == Variant::Chess960 {
+        headers.push_str("[Variant \"Chess960\"]\n");
+    }
+    Ok(format!("{}\n{} *\n", headers, movetext.join(" ")))
+}
+
+/// Whether the side to move has a piece on the square with a legal move.
+fn can_move_from(position: &Chess, square: Square) -> bool {
+    position
+        .legal_moves()
+        .iter()
+        .any(|m| m.from() == Some(square))
+}
+
+/// Shows where the piece on `from` can go and asks for its destination, returning `None` if
+/// there's no such move. The side to move must have a piece on `from` that can move, see
+/// `can_move_from`. Promotions default to a queen, others can be picked with a suffix like
+/// `e8n` or `e8=N`.
+fn pick_destination(
+    from: Square,
+    position: &Chess,
+    lang: Lang,
+    screen: &mut Screen,
+) -> Result<Option<Move>> {
+    let moves = position
+        .legal_moves()
+        .into_iter()
+        .filter(|m| m.from() == Some(from))
+        .collect::<Vec<Move>>();
+    let turn = position.turn();
+    // Castling moves go to the rook's square, but the king's destination is what people type
+    let destination = |m: &Move| {
+        m.castling_side()
+            .map(|side| side.king_to(turn))
+            .unwrap_or_else(|| m.to())
+    };
+    let mut destinations = moves
+        .iter()
+        .map(|m| destination(m).to_string())
+        .collect::<Vec<String>>();
+    destinations.dedup();
+    screen.message(lang.destinations(moves[0].role(), from, &destinations.join(", ")));
+    let reply = screen.prompt(lang.destination_prompt())?;
+    let reply = reply.trim().to_lowercase().replace('=', "");
+    let to = reply
+        .get(..2)
+        .and_then(|square| square.parse::<Square>().ok());
+    let promotion = reply
+        .chars()
+        .nth(2)
+        .and_then(Role::from_char)
+        .unwrap_or(Role::Queen);
+    let m = moves
+        .into_iter()
+        .find(|m| Some(destination(m)) == to && m.promotion().is_none_or(|role| role == promotion));
+    if m.is_none() {
+        screen.message(lang.not_a_destination(&reply));
+    }
+    Ok(m)
+}
+
+/// Asks for the tactic's theme from a numbered list of choices, returning whether the guess was
+/// one of its motif tags. Themes can be picked by number, tag or localized name. Tactics without
+/// any motif tags aren't asked about, so this returns `None` for them.
+fn guess_theme(tactic: &ChessTactic, lang: Lang, screen: &mut Screen) -> Result<Option<bool>> {
+    let themes = motifs::themes(&tactic.tags);
+    if themes.is_empty() {
+        return Ok(None);
+    }
+    let choices = motifs::theme_choices(&tactic.id, &tactic.tags, lang);
+    screen.gap();
+    for (i, choice) in choices.iter().enumerate() {
+        screen.message(format!("  {}. {}", i + 1, motifs::theme_name(choice, lang)));
+    }
+    let reply = screen.prompt(lang.theme_prompt())?;
+    let guess = match reply.trim().parse::<usize>() {
+        Ok(number) if (1..=choices.len()).contains(&number) => choices[number - 1],
+        _ => reply.trim(),
+    };
+    let correct = themes
+        .iter()
+        .any(|theme| motifs::names_theme(guess, theme, lang));
+    let themes = themes
+        .iter()
+        .map(|theme| motifs::theme_name(theme, lang))
+        .collect::<Vec<&str>>()
+        .join(", ");
+    if correct {
+        screen.message(format!("{} {}", lang.correct(), lang.themes_were(&themes)));
+    } else {
+        screen.message(lang.wrong_theme(motifs::theme_name(guess, lang), &themes));
+    }
+    Ok(Some(correct))
 }
 
 enum PromptResponse {
@@ -163,104 +750,149 @@ enum PromptResponse {
     PrintFen,
     Help,
     ShowRating,
+    Skip,
+    Hint,
+    CopyFen,
+    CopyPgn,
+    Describe(Square),
     Move(String),
 }
 
-fn get_prompt_response(position: &Chess) -> PromptResponse {
-    let reply = rprompt::prompt_reply_stdout(&get_prompt(position)).unwrap();
-    match reply.as_ref() {
-        "s" | "show" => return PromptResponse::ShowBoard,
-        "f" | "fen" => return PromptResponse::PrintFen,
-        "?" | "help" => return PromptResponse::Help,
-        "r" | "rating" => return PromptResponse::ShowRating,
-        // "h" | "hint" => return PromptResponse::NoResponse,
-        "" => return PromptResponse::NoResponse,
-        x => return PromptResponse::Move(x.to_string()),
-    }
+fn get_prompt_response(
+    position: &Chess,
+    lang: Lang,
+    screen: &Screen,
+    kids: bool,
+) -> Result<PromptResponse> {
+    let prompt = if kids {
+        lang.kids_prompt(position.turn())
+    } else {
+        lang.prompt(position.turn())
+    };
+    let reply = screen.prompt(&prompt)?;
+    let response = match reply.as_ref() {
+        "s" | "show" => PromptResponse::ShowBoard,
+        "f" | "fen" => PromptResponse::PrintFen,
+        "?" | "help" => PromptResponse::Help,
+        "r" | "rating" => PromptResponse::ShowRating,
+        "n" | "skip" => PromptResponse::Skip,
+        "h" | "hint" => PromptResponse::Hint,
+        "copy fen" => PromptResponse::CopyFen,
+        "copy pgn" => PromptResponse::CopyPgn,
+        "" => PromptResponse::NoResponse,
+        x => match x
+            .strip_prefix("describe ")
+            .map(|s| s.trim().parse::<Square>())
+        {
+            Some(Ok(square)) => PromptResponse::Describe(square),
+            _ => PromptResponse::Move(x.to_string()),
+        },
+    };
+    Ok(response)
 }
 
-#[derive(Deserialize, Debug)]
-pub struct ChessTactic {
-    pub id: String,
-    pub moves: Vec<String>,
-    pub fen: String,
-    pub popularity: i32,
-    pub tags: Vec<String>,
-    pub game_link: String,
-    pub rating: i32,
-    pub rating_deviation: i32,
-    pub number_plays: i32,
-}
+const MAX_FETCH_ATTEMPTS: usize = 20;
 
-#[derive(Serialize, Debug)]
-struct ChessTacticRequest {
-    rating_gte: Option<i32>,
-    rating_lte: Option<i32>,
-    tags: Vec<String>,
+/// Fetches tactics until one passes the request's filters. Tactics that can't be played through
+/// are skipped, and reported to the server if `report_broken` is set.
+async fn get_matching_puzzle(
+    client: &api::Client,
+    request: &ChessTacticRequest,
+    seen: Option<&SeenPuzzles>,
+    report_broken: bool,
+    lang: Lang,
+    screen: &mut Screen,
+) -> Result<ChessTactic> {
+    for _ in 0..MAX_FETCH_ATTEMPTS {
+        let tactic = client
+            .get_tactic(request, screen)
+            .await
+            .context("Failed to get a new tactic from the server")?;
+        if let Err(err) = validate(&tactic, request.variant) {
+            screen.message(lang.broken_tactic(&tactic.id, &err.to_string()));
+            if report_broken {
+                client.report_broken(&tactic.id, &err.to_string()).await;
+            }
+            continue;
+        }
+        let mates_in_time = request
+            .mate_in
+            .is_none_or(|moves| mates_in(&tactic, request.variant, moves));
+        let is_repeat = seen.is_some_and(|seen| seen.contains(&tactic.id));
+        if request.accepts(&tactic) && mates_in_time && !is_repeat {
+            return Ok(tactic);
+        }
+    }
+    Err(anyhow!(
+        "Couldn't find a tactic matching your filters after {} tries",
+        MAX_FETCH_ATTEMPTS
+    ))
 }
 
-async fn get_new_puzzle(request: ChessTacticRequest) -> Result<ChessTactic> {
-    let client = reqwest::Client::new();
-    let tactic: ChessTactic = client
-        .post(get_api_endpoint())
-        .header("User-Agent", "tactics-trainer-cli")
-        .json(&request)
-        .send()
-        .await?
-        .json()
-        .await?;
-    // dbg!(&tactic);
-    return Ok(tactic);
-}
+/// The longest mate with its own puzzle tag.
+const MAX_MATE_IN: usize = 5;
 
-fn get_api_endpoint() -> String {
-    return format!(
-        "{}/api/v1/tactic",
-        env::var("TACTICS_SERVER_URL").unwrap_or("https://chessmadra.com".to_string())
-    );
+/// Whether the tactic's solution is a mate in exactly this many moves. Tags can be wrong, so the
+/// solution is played through to check it really is that long and ends in checkmate.
+fn mates_in(tactic: &ChessTactic, variant: Variant, moves: usize) -> bool {
+    // The solver makes every other move, starting and ending the line after any setup move
+    if tactic.moves.len() != tactic.setup_moves() + moves * 2 - 1 {
+        return false;
+    }
+    replay(tactic, variant).is_ok_and(|position| position.is_checkmate())
 }
 
-fn print_side(side: &Color) -> String {
-    if side == &Color::White {
-        "White".to_string()
-    } else {
-        "Black".to_string()
+/// Checks that the tactic's FEN is a legal position, every move in its line is legal, and it
+/// has at least one move for the solver and ends on one, so the solve loop can play through it.
+fn validate(tactic: &ChessTactic, variant: Variant) -> Result<()> {
+    if tactic.moves.len() <= tactic.setup_moves() {
+        bail!("it has no moves to solve");
     }
+    if (tactic.moves.len() - tactic.setup_moves()) % 2 == 0 {
+        bail!("its line ends on the opponent's move");
+    }
+    replay(tactic, variant)?;
+    Ok(())
 }
 
-fn get_prompt(position: &Chess) -> String {
-    let side = if position.turn() == Color::White {
-        "White"
-    } else {
-        "Black"
-    };
-    return format!("{} to move, enter the best move, or '?' for help: ", side);
+/// Plays through the tactic's whole line, returning the final position.
+fn replay(tactic: &ChessTactic, variant: Variant) -> Result<Chess> {
+    let setup: Fen = tactic
+        .fen
+        .parse()
+        .with_context(|| format!("{} isn't a valid FEN", tactic.fen))?;
+    let mut position: Chess = setup
+        .position(variant.castling_mode())
+        .with_context(|| format!("{} isn't a legal position", tactic.fen))?;
+    for (i, m) in tactic.moves.iter().enumerate() {
+        let legal = m
+            .parse::<Uci>()
+            .ok()
+            .and_then(|uci| uci.to_move(&position).ok());
+        match legal {
+            Some(legal) => position = position.play(&legal)?,
+            None => bail!("move {} ({}) isn't legal", i + 1, m),
+        }
+    }
+    Ok(position)
 }
 
-fn print_help() {
-    ptable!(
-        [
-            "Any move, ex. Qxd7",
-            "Attempt to solve the tactic with the given move."
-        ],
-        [
-            "No input",
-            "Reveal the answer, and continue the tactic if there are more moves."
-        ],
-        [
-            "'f' or 'fen'",
-            "Print out the current board, in FEN notation."
-        ],
-        ["'s' or 'show'", "Show the current board."],
-        ["'r' or 'rating'", "Show the rating of the current tactic."],
-        ["'?' or 'help'", "Display this help."]
-    );
+fn print_help(lang: Lang, kids: bool, screen: &mut Screen) {
+    let mut table = Table::new();
+    for (input, description) in lang.help_rows(kids) {
+        table.add_row(row![input, description]);
+    }
+    screen.message(table.to_string().trim_end().to_string());
 }
 
-fn print_board(position: &Chess) {
+/// The rows of a drawn board, followed by the file letters. Every line is the same width, so
+/// boards can be put side by side.
+fn board_lines(position: &Chess, highlight: &[Square], style: BoardStyle) -> Vec<String> {
+    let palette = style.palette;
     let board: &Board = position.board();
+    let mut lines = vec![];
     for row in 0..8 {
-        print!("  {}  ", 8 - row);
+        let mut line = format!("  {}  ", 8 - row);
         for col in 0..8 {
             let idx = 64 - (row + 1) * 8 + col;
             // dbg!(idx);
@@ -268,35 +900,46 @@ fn print_board(position: &Chess) {
             // dbg!(square);
             let piece = board.piece_at(square);
             let square_is_white = (row + col) % 2 == 0;
-            let c = if square_is_white { 140 } else { 80 };
+            let c = if square_is_white {
+                palette.light_square
+            } else {
+                palette.dark_square
+            };
             let piece_char = piece
                 .map(|p: Piece| {
-                    let ch = piece_ascii(&p);
+                    let ch = if style.figurines {
+                        piece_unicode(&p).to_string()
+                    } else {
+                        piece_ascii(&p)
+                    };
                     let ch = if p.color == Color::White {
-                        ch.blue()
+                        ch.color(palette.white_piece)
                     } else {
-                        ch.red()
+                        ch.color(palette.black_piece)
                     };
                     ch
                 })
                 .unwrap_or("·".to_string().truecolor(c, c, c));
-            if square_is_white {
-                print!("{} ", piece_char);
+            let piece_char = if highlight.contains(&square) {
+                let (r, g, b) = palette.highlight;
+                piece_char.on_truecolor(r, g, b)
             } else {
-                print!("{} ", piece_char);
-            }
+                piece_char
+            };
+            line.push_str(&format!("{} ", piece_char));
         }
-        println!();
+        lines.push(line);
     }
 
-    println!(
-        "     {}",
+    lines.push(format!(
+        "     {} ",
         (b'a'..=b'h')
             .map(char::from)
             .map(|c| c.to_string())
             .collect::<Vec<String>>()
             .join(" ")
-    )
+    ));
+    lines
 }
 
 fn piece_unicode(piece: &Piece) -> &'static str {
@@ -340,3 +983,202 @@ fn piece_ascii(piece: &Piece) -> String {
     // (shakmaty::Role::King, shakmaty::Color::White) => {"♚"}
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mock_server::{MockServer, Reply, Request};
+    use crate::screen::testing::screen;
+
+    /// A ladder mate: after Black's Kg8, White plays Ra7 and, after Kf8, mates with Rb8.
+    fn ladder_mate() -> serde_json::Value {
+        json!({
+            "id": "ladder",
+            "fen": "7k/8/8/8/8/8/1R6/R5K1 b - - 0 1",
+            "moves": ["h8g8", "a1a7", "g8f8", "b2b8"],
+            "tags": ["mateIn2", "endgame"]
+        })
+    }
+
+    fn request() -> ChessTacticRequest {
+        ChessTacticRequest {
+            rating_gte: None,
+            rating_lte: None,
+            tags: vec![],
+            all_tags: false,
+            filter: None,
+            mate_in: None,
+            exclude_tags: vec![],
+            rating_deviation_lte: None,
+            popularity_gte: None,
+            number_plays_gte: None,
+            variant: Variant::Standard,
+        }
+    }
+
+    fn options() -> SolveOptions {
+        SolveOptions {
+            name_the_theme: false,
+            hints_left: None,
+            square_input: false,
+            variant: Variant::Standard,
+            streak: None,
+        }
+    }
+
+    #[test]
+    fn rejects_lines_ending_on_the_opponents_move() {
+        let mut tactic: ChessTactic = serde_json::from_value(ladder_mate()).unwrap();
+        tactic.moves.pop();
+        tactic.solver_moves_first = Some(false);
+        assert!(validate(&tactic, Variant::Standard).is_err());
+
+        // Solving it anyway fails once the line runs out, instead of panicking
+        let (mut screen, _) = screen(&["Ra7"]);
+        let position = puzzle_position(&tactic, Variant::Standard).unwrap();
+        assert!(solve(&tactic, position, Lang::En, &mut screen, &mut options()).is_err());
+    }
+
+    #[test]
+    fn explains_the_streak_in_kids_mode_help() {
+        let tactic: ChessTactic = serde_json::from_value(ladder_mate()).unwrap();
+        let (mut screen, output) = screen(&["?", "Ra7", "Rb8"]);
+        let mut options = SolveOptions {
+            streak: Some(0),
+            ..options()
+        };
+        let position = puzzle_position(&tactic, Variant::Standard).unwrap();
+        solve(&tactic, position, Lang::En, &mut screen, &mut options).unwrap();
+        let output = output.borrow();
+        assert!(output.contains("good moves you've made in a row"));
+        assert!(!output.contains("rating of the current tactic"));
+    }
+
+    #[test]
+    fn types_out_demo_solutions() {
+        let tactic: ChessTactic = serde_json::from_value(ladder_mate()).unwrap();
+        let (mut screen, output) = screen(&[]);
+        let position = puzzle_position(&tactic, Variant::Standard).unwrap();
+        let lang = Lang::En;
+        demo::play(&tactic, position, lang, &mut screen, Duration::ZERO).unwrap();
+        let prompt = lang.prompt(Color::White);
+        let expected = format!(
+            "\n{prompt}Ra7\n\n{correct} {response}\n\n{prompt}Rb8\n\n{correct} {completed}\n",
+            prompt = prompt,
+            correct = lang.correct(),
+            response = lang.responds_with(Color::Black, "Kf8"),
+            completed = lang.completed(),
+        );
+        assert_eq!(*output.borrow(), expected);
+    }
+
+    #[test]
+    fn reads_moves_typed_in_square_input_mode() {
+        let tactic: ChessTactic = serde_json::from_value(json!({
+            "id": "opening",
+            "fen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "moves": ["e2e4"]
+        }))
+        .unwrap();
+        let (mut screen, output) = screen(&["d2", "d4", "e4"]);
+        let mut options = SolveOptions {
+            square_input: true,
+            ..options()
+        };
+        let position = puzzle_position(&tactic, Variant::Standard).unwrap();
+        match solve(&tactic, position, Lang::En, &mut screen, &mut options).unwrap() {
+            Outcome::Completed(score, _) => assert_eq!(score.moves_correct, 1),
+            Outcome::Skipped => panic!("The tactic was skipped instead of played through"),
+        }
+        assert!(output.borrow().contains(&Lang::En.incorrect_move("d4")));
+    }
+
+    fn tactic_tagged(tags: &[&str]) -> ChessTactic {
+        let mut tactic: ChessTactic = serde_json::from_value(ladder_mate()).unwrap();
+        tactic.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        tactic
+    }
+
+    #[test]
+    fn names_themes_by_number_tag_or_localized_name() {
+        let tactic = tactic_tagged(&["fork", "middlegame"]);
+        let choices = motifs::theme_choices(&tactic.id, &tactic.tags, Lang::En);
+        let answer = (choices.iter().position(|&c| c == "fork").unwrap() + 1).to_string();
+        let wrong = (choices.iter().position(|&c| c != "fork").unwrap() + 1).to_string();
+        let replies = [
+            answer.as_str(),
+            wrong.as_str(),
+            "FORK",
+            "Gabel",
+            "Fesselung",
+        ];
+        let (mut screen, output) = screen(&replies);
+        let mut guess = |lang: Lang| guess_theme(&tactic, lang, &mut screen).unwrap();
+        assert_eq!(guess(Lang::En), Some(true));
+        assert_eq!(guess(Lang::En), Some(false));
+        assert_eq!(guess(Lang::En), Some(true));
+        assert_eq!(guess(Lang::De), Some(true));
+        assert_eq!(guess(Lang::De), Some(false));
+        assert!(output.borrow().contains(&format!("  {}. Fork", answer)));
+    }
+
+    #[test]
+    fn offers_wrong_choices_from_the_whole_pool() {
+        let tactic = tactic_tagged(&["skewer", "middlegame"]);
+        let choices = motifs::theme_choices(&tactic.id, &tactic.tags, Lang::En);
+        assert_eq!(choices.len(), 5);
+        assert_eq!(choices.iter().filter(|&&c| c == "skewer").count(), 1);
+        let answer = (choices.iter().position(|&c| c == "skewer").unwrap() + 1).to_string();
+        let (mut screen, _) = screen(&[answer.as_str(), "skewer"]);
+        let mut guess = || guess_theme(&tactic, Lang::En, &mut screen).unwrap();
+        assert_eq!(guess(), Some(true));
+        assert_eq!(guess(), Some(true));
+    }
+
+    #[test]
+    fn skips_naming_the_theme_without_motif_tags() {
+        let tactic = tactic_tagged(&["middlegame", "long"]);
+        let (mut screen, output) = screen(&[]);
+        assert_eq!(guess_theme(&tactic, Lang::En, &mut screen).unwrap(), None);
+        assert!(output.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn plays_fetched_tactics_through() {
+        let server = MockServer::start().await;
+        let tactics = server.mock(
+            Request::post("/api/v1/tactic"),
+            Reply::status(200).json_body(ladder_mate()),
+        );
+        let client = api::Client::new(server.base_url(), Lang::En, None, None).unwrap();
+        let (mut screen, output) = screen(&["n", "Rb7", "Ra7", "Rb8"]);
+        let mut options = options();
+
+        let tactic = get_matching_puzzle(&client, &request(), None, false, Lang::En, &mut screen)
+            .await
+            .unwrap();
+        let position = puzzle_position(&tactic, Variant::Standard).unwrap();
+        let outcome = solve(&tactic, position, Lang::En, &mut screen, &mut options).unwrap();
+        assert!(matches!(outcome, Outcome::Skipped));
+
+        let tactic = get_matching_puzzle(&client, &request(), None, false, Lang::En, &mut screen)
+            .await
+            .unwrap();
+        let position = puzzle_position(&tactic, Variant::Standard).unwrap();
+        match solve(&tactic, position, Lang::En, &mut screen, &mut options).unwrap() {
+            Outcome::Completed(score, position) => {
+                assert_eq!(score.moves_correct, 2);
+                assert_eq!(score.moves_total, 2);
+                assert!(position.is_checkmate());
+            }
+            Outcome::Skipped => panic!("The tactic was skipped instead of played through"),
+        }
+        assert_eq!(tactics.hits(), 2);
+        let output = output.borrow();
+        assert!(output.contains(&Lang::En.incorrect_move("Rb7")));
+        assert!(output.contains(&Lang::En.responds_with(Color::Black, "Kf8")));
+        assert!(output.contains(Lang::En.completed()));
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use serde_json::Value;
+use tokio::{
+    io::{self, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+/// An HTTP server for tests, which answers requests with canned replies and counts the requests
+/// each reply was sent for. Requests that nothing matches get a 404.
+pub struct MockServer {
+    address: SocketAddr,
+    routes: Arc<Mutex<Vec<Route>>>,
+}
+
+struct Route {
+    id: usize,
+    request: Request,
+    reply: Reply,
+    hits: usize,
+}
+
+/// What a request has to look like for a mock to answer it.
+pub struct Request {
+    method: &'static str,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    json: Option<Value>,
+}
+
+/// What a mock answers with.
+#[derive(Clone)]
+pub struct Reply {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+/// A mock set up on a server, to check how many requests it answered.
+pub struct Mock {
+    id: usize,
+    routes: Arc<Mutex<Vec<Route>>>,
+}
+
+/// A request as the server read it.
+struct Received {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl MockServer {
+    /// Starts the server on a free port, serving requests in the background.
+    pub async fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let routes = Arc::new(Mutex::new(vec![]));
+        let server = MockServer {
+            address,
+            routes: routes.clone(),
+        };
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, routes.clone()));
+            }
+        });
+        server
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Answers requests like the given one with the reply. Mocks set up first are matched first.
+    pub fn mock(&self, request: Request, reply: Reply) -> Mock {
+        let mut routes = self.routes.lock().unwrap();
+        let id = routes.iter().map(|route| route.id + 1).max().unwrap_or(0);
+        routes.push(Route {
+            id,
+            request,
+            reply,
+            hits: 0,
+        });
+        Mock {
+            id,
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+impl Request {
+    pub fn get(path: &str) -> Request {
+        Request::new("GET", path)
+    }
+
+    pub fn post(path: &str) -> Request {
+        Request::new("POST", path)
+    }
+
+    fn new(method: &'static str, path: &str) -> Request {
+        Request {
+            method,
+            path: path.to_string(),
+            query: vec![],
+            headers: vec![],
+            json: None,
+        }
+    }
+
+    pub fn query_param(mut self, name: &str, value: &str) -> Request {
+        self.query.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Request {
+        self.headers.push((name.to_lowercase(), value.to_string()));
+        self
+    }
+
+    /// Only matches requests whose body is exactly this JSON.
+    pub fn json_body(mut self, json: Value) -> Request {
+        self.json = Some(json);
+        self
+    }
+
+    fn matches(&self, received: &Received) -> bool {
+        self.method == received.method
+            && self.path == received.path
+            && self.query.iter().all(|pair| received.query.contains(pair))
+            && self
+                .headers
+                .iter()
+                .all(|(name, value)| received.headers.get(name) == Some(value))
+            && self.json.as_ref().is_none_or(|json| {
+                serde_json::from_slice::<Value>(&received.body).is_ok_and(|body| body == *json)
+            })
+    }
+}
+
+impl Reply {
+    pub fn status(status: u16) -> Reply {
+        Reply {
+            status,
+            headers: vec![],
+            body: String::new(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Reply {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: &str) -> Reply {
+        self.body = body.to_string();
+        self
+    }
+
+    pub fn json_body(self, json: Value) -> Reply {
+        self.header("Content-Type", "application/json")
+            .body(&json.to_string())
+    }
+
+    fn to_http(&self) -> String {
+        let mut http = format!(
+            "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\n",
+            self.status,
+            self.body.len()
+        );
+        for (name, value) in &self.headers {
+            http.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        http.push_str("\r\n");
+        http.push_str(&self.body);
+        http
+    }
+}
+
+impl Mock {
+    /// How many requests the mock has answered.
+    pub fn hits(&self) -> usize {
+        let routes = self.routes.lock().unwrap();
+        routes
+            .iter()
+            .find(|route| route.id == self.id)
+            .map_or(0, |route| route.hits)
+    }
+
+    /// Stops answering requests, so that mocks set up after it get to answer them.
+    pub fn delete(self) {
+        self.routes
+            .lock()
+            .unwrap()
+            .retain(|route| route.id != self.id);
+    }
+}
+
+/// Answers the requests sent over a connection until the client closes it.
+async fn serve(stream: TcpStream, routes: Arc<Mutex<Vec<Route>>>) -> io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    while let Some(received) = read_request(&mut stream).await? {
+        let reply = {
+            let mut routes = routes.lock().unwrap();
+            match routes
+                .iter_mut()
+                .find(|route| route.request.matches(&received))
+            {
+                Some(route) => {
+                    route.hits += 1;
+                    route.reply.clone()
+                }
+                None => Reply::status(404),
+            }
+        };
+        stream
+            .get_mut()
+            .write_all(reply.to_http().as_bytes())
+            .await?;
+    }
+    Ok(())
+}
+
+/// Reads the next request on the connection, or `None` once it's closed.
+async fn read_request(stream: &mut BufReader<TcpStream>) -> io::Result<Option<Received>> {
+    let mut request_line = String::new();
+    if stream.read_line(&mut request_line).await? == 0 {
+        return Ok(None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query: Vec<(String, String)> = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        match line.trim_end().split_once(':') {
+            Some((name, value)) => {
+                headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+            }
+            None => break,
+        }
+    }
+    let length = headers
+        .get("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).await?;
+    Ok(Some(Received {
+        method,
+        path: path.to_string(),
+        query,
+        headers,
+        body,
+    }))
+}
@@ -0,0 +1,297 @@
+use anyhow::Result;
+use colored::*;
+use shakmaty::{fen::Fen, san::San, uci::Uci, CastlingMode, Chess, Position};
+
+use crate::i18n::Lang;
+use crate::rng::Rng;
+use crate::screen::Screen;
+
+/// A tactical motif, with a worked example that is played through before a drill on that theme.
+pub struct Motif {
+    /// The puzzle tag this motif explains.
+    pub tag: &'static str,
+    fen: &'static str,
+    moves: &'static [&'static str],
+    en: MotifText,
+    de: MotifText,
+    es: MotifText,
+}
+
+struct MotifText {
+    name: &'static str,
+    description: &'static str,
+    /// One annotation per move of the example.
+    notes: &'static [&'static str],
+}
+
+static MOTIFS: [Motif; 4] = [
+    Motif {
+        tag: "backRankMate",
+        fen: "6k1/5ppp/2n5/8/8/8/5PPP/4R1K1 w - - 0 1",
+        moves: &["e1e8"],
+        en: MotifText {
+            name: "Back rank mate",
+            description: "A king stuck behind its own pawns on the back rank can be mated by a single rook or queen check along that rank. Look for it whenever the defending king has no escape square and the back rank is undefended.",
+            notes: &["The rook checks along the back rank. The f7, g7 and h7 pawns take every escape square, and nothing can block on f8, so it's mate."],
+        },
+        de: MotifText {
+            name: "Grundreihenmatt",
+            description: "Ein König, der hinter seinen eigenen Bauern auf der Grundreihe feststeckt, kann von einem einzigen Turm- oder Damenschach auf dieser Reihe mattgesetzt werden. Achte darauf, wann immer der König kein Fluchtfeld hat und die Grundreihe ungedeckt ist.",
+            notes: &["Der Turm gibt Schach auf der Grundreihe. Die Bauern auf f7, g7 und h7 nehmen dem König alle Fluchtfelder, und nichts kann auf f8 dazwischenziehen – matt."],
+        },
+        es: MotifText {
+            name: "Mate del pasillo",
+            description: "Un rey encerrado detrás de sus propios peones en la primera fila puede recibir mate con un solo jaque de torre o dama en esa fila. Búscalo siempre que el rey no tenga casillas de escape y la última fila esté sin defender.",
+            notes: &["La torre da jaque en la última fila. Los peones de f7, g7 y h7 le quitan todas las casillas al rey y nada puede interponerse en f8: es mate."],
+        },
+    },
+    Motif {
+        tag: "fork",
+        fen: "2q3k1/5ppp/8/3N4/8/8/5PPP/6K1 w - - 0 1",
+        moves: &["d5e7", "g8h8", "e7c8"],
+        en: MotifText {
+            name: "Fork",
+            description: "A single piece attacks two or more targets at once, so the opponent can only save one of them. Knights are the classic forking piece, especially with a check.",
+            notes: &[
+                "The knight checks the king and attacks the queen at the same time.",
+                "The king has to move out of check.",
+                "And the queen falls.",
+            ],
+        },
+        de: MotifText {
+            name: "Gabel",
+            description: "Eine einzelne Figur greift zwei oder mehr Ziele gleichzeitig an, sodass der Gegner nur eines davon retten kann. Springer sind die klassischen Gabelfiguren, besonders mit Schach.",
+            notes: &[
+                "Der Springer gibt Schach und greift gleichzeitig die Dame an.",
+                "Der König muss dem Schach ausweichen.",
+                "Und die Dame fällt.",
+            ],
+        },
+        es: MotifText {
+            name: "Ataque doble",
+            description: "Una sola pieza ataca dos o más objetivos a la vez, así que el rival solo puede salvar uno. El caballo es la pieza clásica para el ataque doble, sobre todo con jaque.",
+            notes: &[
+                "El caballo da jaque al rey y ataca a la dama al mismo tiempo.",
+                "El rey tiene que salir del jaque.",
+                "Y cae la dama.",
+            ],
+        },
+    },
+    Motif {
+        tag: "pin",
+        fen: "6k1/6pp/8/3q4/8/1P6/5PPP/5BK1 w - - 0 1",
+        moves: &["f1c4", "d5c4", "b3c4"],
+        en: MotifText {
+            name: "Pin",
+            description: "A piece can't move off a line without exposing a more valuable piece behind it. When the piece behind is the king, the pinned piece can't leave the line at all, so it can be attacked and won.",
+            notes: &[
+                "The bishop pins the queen to the king on g8. The queen can't leave the diagonal.",
+                "Black's best is to give up the queen for the bishop.",
+                "The pawn recaptures, and White has won the queen for a bishop.",
+            ],
+        },
+        de: MotifText {
+            name: "Fesselung",
+            description: "Eine Figur kann eine Linie nicht verlassen, ohne eine wertvollere Figur dahinter preiszugeben. Steht dahinter der König, darf die gefesselte Figur die Linie gar nicht verlassen und kann angegriffen und gewonnen werden.",
+            notes: &[
+                "Der Läufer fesselt die Dame an den König auf g8. Die Dame kann die Diagonale nicht verlassen.",
+                "Schwarz gibt am besten die Dame für den Läufer.",
+                "Der Bauer schlägt zurück, und Weiß hat die Dame gegen einen Läufer gewonnen.",
+            ],
+        },
+        es: MotifText {
+            name: "Clavada",
+            description: "Una pieza no puede salir de una línea sin dejar expuesta una pieza más valiosa detrás. Si detrás está el rey, la pieza clavada no puede salir de la línea en absoluto, así que se la puede atacar y ganar.",
+            notes: &[
+                "El alfil clava la dama contra el rey de g8. La dama no puede salir de la diagonal.",
+                "Lo mejor para las negras es entregar la dama por el alfil.",
+                "El peón recaptura y las blancas ganan la dama a cambio de un alfil.",
+            ],
+        },
+    },
+    Motif {
+        tag: "smotheredMate",
+        fen: "6rk/6pp/8/6N1/8/8/8/6K1 w - - 0 1",
+        moves: &["g5f7"],
+        en: MotifText {
+            name: "Smothered mate",
+            description: "A knight mates a king that is completely surrounded by its own pieces. It usually happens in the corner, after the defender's pieces have blocked every escape square.",
+            notes: &["The knight checks the king on h8. The rook and pawns take every escape square, and nothing can capture on f7, so it's mate."],
+        },
+        de: MotifText {
+            name: "Ersticktes Matt",
+            description: "Ein Springer setzt einen König matt, der vollständig von seinen eigenen Figuren umgeben ist. Meist passiert das in der Ecke, nachdem die Figuren des Verteidigers alle Fluchtfelder verstellt haben.",
+            notes: &["Der Springer gibt dem König auf h8 Schach. Turm und Bauern versperren alle Fluchtfelder, und niemand kann auf f7 schlagen – matt."],
+        },
+        es: MotifText {
+            name: "Mate de la coz",
+            description: "Un caballo da mate a un rey completamente rodeado por sus propias piezas. Suele ocurrir en la esquina, después de que las piezas del defensor hayan bloqueado todas las casillas de escape.",
+            notes: &["El caballo da jaque al rey de h8. La torre y los peones le quitan todas las casillas y nada puede capturar en f7: es mate."],
+        },
+    },
+];
+
+/// Tags that describe the game phase, the puzzle's length or its source rather than a tactical
+/// motif.
+const NON_MOTIF_TAGS: [&str; 20] = [
+    "advantage",
+    "crushing",
+    "equality",
+    "mate",
+    "opening",
+    "middlegame",
+    "endgame",
+    "pawnEndgame",
+    "knightEndgame",
+    "bishopEndgame",
+    "rookEndgame",
+    "queenEndgame",
+    "queenRookEndgame",
+    "short",
+    "long",
+    "veryLong",
+    "oneMove",
+    "master",
+    "masterVsMaster",
+    "superGM",
+];
+
+/// The tags of a tactic that name a motif, ex. `fork` but not `middlegame`.
+pub fn themes(tags: &[String]) -> Vec<&str> {
+    tags.iter()
+        .map(|tag| tag.as_str())
+        .filter(|tag| !NON_MOTIF_TAGS.contains(tag))
+        .collect()
+}
+
+/// The name of a theme in the language, for the motifs that have one, or else its tag.
+pub fn theme_name(tag: &str, lang: Lang) -> &str {
+    match find(tag) {
+        Some(motif) => motif.text(lang).name,
+        None => tag,
+    }
+}
+
+/// Whether the guess names the theme, either by its tag or by its name in the language.
+pub fn names_theme(guess: &str, tag: &str, lang: Lang) -> bool {
+    guess.eq_ignore_ascii_case(tag) || guess.to_lowercase() == theme_name(tag, lang).to_lowercase()
+}
+
+/// The motif tags Lichess puzzles can have, which the wrong choices are drawn from when asked to
+/// name a tactic's theme. Drawing them from every motif, not just those with worked examples,
+/// keeps the right answer from standing out.
+const THEME_POOL: [&str; 38] = [
+    "advancedPawn",
+    "anastasiaMate",
+    "arabianMate",
+    "attackingF2F7",
+    "attraction",
+    "backRankMate",
+    "bodenMate",
+    "capturingDefender",
+    "castling",
+    "clearance",
+    "defensiveMove",
+    "deflection",
+    "discoveredAttack",
+    "doubleBishopMate",
+    "doubleCheck",
+    "dovetailMate",
+    "enPassant",
+    "exposedKing",
+    "fork",
+    "hangingPiece",
+    "hookMate",
+    "interference",
+    "intermezzo",
+    "kingsideAttack",
+    "mateIn1",
+    "mateIn2",
+    "mateIn3",
+    "pin",
+    "promotion",
+    "queensideAttack",
+    "quietMove",
+    "sacrifice",
+    "skewer",
+    "smotheredMate",
+    "trappedPiece",
+    "underPromotion",
+    "xRayAttack",
+    "zugzwang",
+];
+
+/// How many choices are offered when asked to name a tactic's theme.
+const THEME_CHOICES: usize = 5;
+
+/// The choices offered when asked to name a tactic's theme: one of its themes, and motifs from
+/// `THEME_POOL` it isn't tagged with, sorted by name so the answer's position gives nothing away.
+/// The picks are seeded by the tactic's id, so a tactic always gets the same choices.
+pub fn theme_choices<'a>(id: &str, tags: &'a [String], lang: Lang) -> Vec<&'a str> {
+    let themes = themes(tags);
+    if themes.is_empty() {
+        return vec![];
+    }
+    let seed = id.bytes().fold(0u64, |seed, byte| {
+        seed.wrapping_mul(31).wrapping_add(byte as u64)
+    });
+    let mut rng = Rng::new(Some(seed));
+    // Themes outside the pool would stand out among the wrong choices, so they're only the
+    // answer when the tactic has no other
+    let pooled = themes
+        .iter()
+        .copied()
+        .filter(|theme| THEME_POOL.iter().any(|pooled| pooled == theme))
+        .collect::<Vec<&str>>();
+    let answers = if pooled.is_empty() { &themes } else { &pooled };
+    let mut choices = vec![answers[rng.below(answers.len())]];
+    let mut wrong = THEME_POOL
+        .iter()
+        .copied()
+        .filter(|theme| !tags.iter().any(|tag| tag == theme))
+        .collect::<Vec<&str>>();
+    while choices.len() < THEME_CHOICES && !wrong.is_empty() {
+        choices.push(wrong.swap_remove(rng.below(wrong.len())));
+    }
+    choices.sort_by_key(|tag| theme_name(tag, lang).to_lowercase());
+    choices
+}
+
+pub fn find(tag: &str) -> Option<&'static Motif> {
+    MOTIFS.iter().find(|motif| motif.tag == tag)
+}
+
+impl Motif {
+    fn text(&self, lang: Lang) -> &MotifText {
+        match lang {
+            Lang::En => &self.en,
+            Lang::De => &self.de,
+            Lang::Es => &self.es,
+        }
+    }
+}
+
+/// Explains the motif and plays through its example with annotations, waiting for the user
+/// before the drill starts.
+pub fn warmup(motif: &Motif, lang: Lang, screen: &mut Screen) -> Result<()> {
+    let text = motif.text(lang);
+    screen.gap();
+    screen.message(text.name.bold().to_string());
+    screen.message(text.description.to_string());
+    screen.gap();
+    let setup: Fen = motif.fen.parse()?;
+    let mut position: Chess = setup.position(CastlingMode::Standard)?;
+    screen.print_board(&position);
+    screen.gap();
+    for (uci, note) in motif.moves.iter().zip(text.notes) {
+        let m = uci.parse::<Uci>()?.to_move(&position)?;
+        let san = screen.san(&San::from_move(&position, &m).to_string());
+        screen.message(lang.demo_move(position.turn(), &san, note));
+        position = position.play(&m)?;
+    }
+    screen.gap();
+    screen.print_board(&position);
+    screen.gap();
+    screen.prompt(lang.press_enter())?;
+    Ok(())
+}
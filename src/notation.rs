@@ -0,0 +1,102 @@
+use clap::ArgEnum;
+use shakmaty::{san::SanPlus, Chess, Move, Role};
+
+use crate::i18n::Lang;
+
+/// How moves are written in the output.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Notation {
+    Figurine,
+    English,
+    German,
+    Spanish,
+}
+
+impl Notation {
+    /// The notation with the language's piece letters.
+    pub fn for_lang(lang: Lang) -> Notation {
+        match lang {
+            Lang::En => Notation::English,
+            Lang::De => Notation::German,
+            Lang::Es => Notation::Spanish,
+        }
+    }
+
+    /// Rewrites English SAN in this notation, ex. Nf3 as ♘f3 or Sf3. Anything else in the text,
+    /// like move numbers, is left alone, since only piece letters are uppercase.
+    pub fn format(&self, san: &str) -> String {
+        san.chars()
+            .map(|c| match letter_role(c) {
+                Some(role) => match self {
+                    Notation::Figurine => figurine(role),
+                    Notation::English => c,
+                    Notation::German => Lang::De.role_letter(role),
+                    Notation::Spanish => Lang::Es.role_letter(role),
+                },
+                None => c,
+            })
+            .collect()
+    }
+}
+
+/// Rewrites a move typed by the user into standard English SAN. Figurines are replaced with
+/// piece letters, and localized piece letters are translated for the given language.
+pub fn normalize_input(input: &str, lang: Lang) -> String {
+    let letters = input
+        .trim()
+        .chars()
+        .filter(|c| *c != '\u{fe0e}' && *c != '\u{fe0f}')
+        .filter_map(|c| match figurine_role(c) {
+            Some(Role::Pawn) => None,
+            Some(role) => Some(role.upper_char()),
+            None => Some(c),
+        })
+        .collect::<String>();
+    lang.normalize_san(&letters)
+}
+
+/// Parses a move typed by the user in the given position, returning `None` if it isn't a legal
+/// move.
+pub fn parse_move(input: &str, position: &Chess, lang: Lang) -> Option<Move> {
+    normalize_input(input, lang)
+        .parse::<SanPlus>()
+        .ok()?
+        .san
+        .to_move(position)
+        .ok()
+}
+
+fn figurine_role(c: char) -> Option<Role> {
+    match c {
+        '♔' | '♚' => Some(Role::King),
+        '♕' | '♛' => Some(Role::Queen),
+        '♖' | '♜' => Some(Role::Rook),
+        '♗' | '♝' => Some(Role::Bishop),
+        '♘' | '♞' => Some(Role::Knight),
+        '♙' | '♟' => Some(Role::Pawn),
+        _ => None,
+    }
+}
+
+fn figurine(role: Role) -> char {
+    match role {
+        Role::King => '♔',
+        Role::Queen => '♕',
+        Role::Rook => '♖',
+        Role::Bishop => '♗',
+        Role::Knight => '♘',
+        Role::Pawn => '♙',
+    }
+}
+
+/// The piece named by an English SAN letter. Pawns don't have one.
+fn letter_role(c: char) -> Option<Role> {
+    match c {
+        'K' => Some(Role::King),
+        'Q' => Some(Role::Queen),
+        'R' => Some(Role::Rook),
+        'B' => Some(Role::Bishop),
+        'N' => Some(Role::Knight),
+        _ => None,
+    }
+}
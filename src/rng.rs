@@ -0,0 +1,35 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A small xorshift generator. It's seeded from `--seed` when given, so that runs with the same
+/// seed make the same random choices, and from the clock otherwise.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: Option<u64>) -> Rng {
+        match seed {
+            // Spread out small seeds, which xorshift takes a while to get going from
+            Some(seed) => Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1),
+            None => Rng::from_time(),
+        }
+    }
+
+    fn from_time() -> Rng {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Rng(nanos | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        // xorshift64
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
@@ -0,0 +1,298 @@
+use std::io::{self, Write};
+
+use anyhow::Result;
+use shakmaty::{Chess, Move, Square};
+
+use crate::board_lines;
+use crate::clipboard::Clipboard;
+use crate::describe::describe_position;
+use crate::i18n::Lang;
+use crate::notation::Notation;
+use crate::theme::BoardStyle;
+
+const LOG_LINES: usize = 6;
+
+pub enum Layout {
+    /// Boards and messages are printed as the tactic goes on.
+    Scrolling,
+    /// The terminal is cleared and the board is redrawn at the top with the latest messages
+    /// underneath, so the prompt stays at the bottom of the board instead of scrolling away.
+    Pinned,
+    /// Boards are described in words instead of drawn, for screen readers.
+    Accessible,
+}
+
+/// Where the screen reads replies from and writes its output to.
+pub trait Terminal {
+    /// Shows the prompt and reads a line, without its line ending.
+    fn read_line(&self, prompt: &str) -> Result<String>;
+    fn write(&self, text: &str);
+}
+
+/// The terminal the trainer is running in.
+pub struct Console;
+
+impl Terminal for Console {
+    fn read_line(&self, prompt: &str) -> Result<String> {
+        Ok(rprompt::prompt_reply_stdout(prompt)?)
+    }
+
+    fn write(&self, text: &str) {
+        let mut stdout = io::stdout();
+        // Flushed right away, so text typed out a character at a time shows up as it's typed
+        let _ = write!(stdout, "{}", text);
+        let _ = stdout.flush();
+    }
+}
+
+/// Where the solve loop's output goes, and where its replies come from.
+pub struct Screen {
+    terminal: Box<dyn Terminal>,
+    layout: Layout,
+    lang: Lang,
+    style: BoardStyle,
+    notation: Notation,
+    position: Option<Chess>,
+    log: Vec<String>,
+    /// A revealed move, drawn as the board before it next to the current board.
+    comparison: Option<Comparison>,
+    clipboard: Clipboard,
+}
+
+struct Comparison {
+    before: Chess,
+    squares: Vec<Square>,
+    /// Whether the comparison should stay up through the next board update, which is the
+    /// opponent's reply to the revealed move.
+    keep: bool,
+}
+
+impl Screen {
+    pub fn new(layout: Layout, lang: Lang, style: BoardStyle, notation: Notation) -> Screen {
+        Screen::with_terminal(Box::new(Console), layout, lang, style, notation)
+    }
+
+    pub fn with_terminal(
+        terminal: Box<dyn Terminal>,
+        layout: Layout,
+        lang: Lang,
+        style: BoardStyle,
+        notation: Notation,
+    ) -> Screen {
+        Screen {
+            terminal,
+            layout,
+            lang,
+            style,
+            notation,
+            position: None,
+            log: vec![],
+            comparison: None,
+            clipboard: Clipboard::default(),
+        }
+    }
+
+    /// Asks for a reply on the terminal.
+    pub fn prompt(&self, prompt: &str) -> Result<String> {
+        self.terminal.read_line(prompt)
+    }
+
+    /// Writes text as is, without starting a new line, ex. a reply being typed out at a prompt.
+    pub fn write(&self, text: &str) {
+        self.terminal.write(text);
+    }
+
+    /// Copies text to the clipboard, which is kept open for the rest of the session.
+    pub fn copy(&mut self, text: String) -> Result<()> {
+        self.clipboard.copy(text)
+    }
+
+    fn line(&self, text: &str) {
+        self.terminal.write(&format!("{}\n", text));
+    }
+
+    /// Writes moves in SAN, or text containing them, in the notation picked for the output.
+    pub fn san(&self, san: &str) -> String {
+        self.notation.format(san)
+    }
+
+    /// Prints a board right away, regardless of the layout.
+    pub fn print_board(&self, position: &Chess) {
+        match self.layout {
+            Layout::Accessible => self.line(&describe_position(position, self.lang)),
+            _ => self.board(position),
+        }
+    }
+
+    /// Shows the board, printing a new one unless the screen is pinned.
+    pub fn show_board(&mut self, position: &Chess) {
+        match self.layout {
+            Layout::Pinned => {
+                self.comparison = None;
+                self.position = Some(position.clone());
+                self.redraw();
+            }
+            _ => {
+                self.line("");
+                self.print_board(position);
+            }
+        }
+    }
+
+    /// Updates the board after a move. Other layouts only print the board on request.
+    pub fn update_board(&mut self, position: &Chess) {
+        if let Layout::Pinned = self.layout {
+            match &mut self.comparison {
+                Some(comparison) if comparison.keep => comparison.keep = false,
+                _ => self.comparison = None,
+            }
+            self.position = Some(position.clone());
+            self.redraw();
+        }
+    }
+
+    /// Shows a revealed move as the boards before and after it, with the squares it moved from
+    /// and to highlighted. Screen readers already hear the move, so nothing is drawn for them.
+    pub fn show_move(&mut self, before: &Chess, after: &Chess, m: &Move) {
+        let squares = m
+            .from()
+            .into_iter()
+            .chain(Some(m.to()))
+            .collect::<Vec<Square>>();
+        match self.layout {
+            Layout::Scrolling => {
+                self.line("");
+                self.board_pair(before, after, &squares);
+            }
+            Layout::Pinned => {
+                self.comparison = Some(Comparison {
+                    before: before.clone(),
+                    squares,
+                    keep: true,
+                });
+                self.position = Some(after.clone());
+                self.redraw();
+            }
+            Layout::Accessible => {}
+        }
+    }
+
+    pub fn message(&mut self, message: String) {
+        match self.layout {
+            Layout::Pinned => {
+                self.log.push(message);
+                if self.log.len() > LOG_LINES {
+                    self.log.remove(0);
+                }
+                self.redraw();
+            }
+            _ => self.line(&message),
+        }
+    }
+
+    /// Prints a blank line between prompts, which pinned screens don't need.
+    pub fn gap(&self) {
+        match self.layout {
+            Layout::Pinned => {}
+            _ => self.line(""),
+        }
+    }
+
+    fn redraw(&self) {
+        // Clear the screen and move the cursor to the top left
+        self.terminal.write("\x1b[2J\x1b[H");
+        self.line("");
+        match (&self.position, &self.comparison) {
+            (Some(position), Some(comparison)) => {
+                self.board_pair(&comparison.before, position, &comparison.squares)
+            }
+            (Some(position), None) => self.board(position),
+            _ => {}
+        }
+        self.line("");
+        for line in &self.log {
+            self.line(line);
+        }
+    }
+
+    fn board(&self, position: &Chess) {
+        for line in board_lines(position, &[], self.style) {
+            self.line(&line);
+        }
+    }
+
+    /// Draws two boards next to each other, ex. before and after a move, with the given squares
+    /// highlighted on both.
+    fn board_pair(&self, before: &Chess, after: &Chess, highlight: &[Square]) {
+        let lines = board_lines(before, highlight, self.style)
+            .into_iter()
+            .zip(board_lines(after, highlight, self.style));
+        for (left, right) in lines {
+            self.line(&format!("{}   {}", left, right));
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod testing {
+    use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+    use anyhow::Result;
+
+    use super::{Layout, Screen, Terminal};
+    use crate::i18n::Lang;
+    use crate::notation::Notation;
+    use crate::theme::{BoardStyle, Theme};
+
+    /// A terminal for tests, which replies with scripted lines and keeps everything written to
+    /// it, prompts and replies included.
+    pub struct Script {
+        replies: RefCell<VecDeque<String>>,
+        output: Rc<RefCell<String>>,
+    }
+
+    impl Script {
+        /// The terminal, and a handle to its output.
+        pub fn new(replies: &[&str]) -> (Script, Rc<RefCell<String>>) {
+            let output = Rc::new(RefCell::new(String::new()));
+            let script = Script {
+                replies: RefCell::new(replies.iter().map(|reply| reply.to_string()).collect()),
+                output: output.clone(),
+            };
+            (script, output)
+        }
+    }
+
+    /// A screen that answers its prompts with the replies, and a handle to what it printed.
+    pub fn screen(replies: &[&str]) -> (Screen, Rc<RefCell<String>>) {
+        let (script, output) = Script::new(replies);
+        let style = BoardStyle {
+            figurines: false,
+            palette: Theme::Dark.palette(),
+        };
+        let screen = Screen::with_terminal(
+            Box::new(script),
+            Layout::Scrolling,
+            Lang::En,
+            style,
+            Notation::English,
+        );
+        (screen, output)
+    }
+
+    impl Terminal for Script {
+        fn read_line(&self, prompt: &str) -> Result<String> {
+            let reply = self
+                .replies
+                .borrow_mut()
+                .pop_front()
+                .ok_or_else(|| anyhow!("Ran out of replies at '{}'", prompt))?;
+            self.write(&format!("{}{}\n", prompt, reply));
+            Ok(reply)
+        }
+
+        fn write(&self, text: &str) {
+            self.output.borrow_mut().push_str(text);
+        }
+    }
+}
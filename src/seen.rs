@@ -0,0 +1,46 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::storage;
+
+const SEEN_FILE: &str = "seen.json";
+
+/// The IDs of the tactics shown in recent runs, saved on disk so that a tactic doesn't come up
+/// again within a window of runs.
+pub struct SeenPuzzles {
+    path: PathBuf,
+    /// The IDs shown in each run, oldest first. The last one is the current run.
+    runs: Vec<Vec<String>>,
+}
+
+impl SeenPuzzles {
+    /// Loads the IDs from the last `window` runs, and starts a new run.
+    pub fn load(window: usize) -> Result<SeenPuzzles> {
+        let path = storage::data_dir()?.join(SEEN_FILE);
+        let mut runs: Vec<Vec<String>> = if path.exists() {
+            let contents = fs::read_to_string(&path)?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Couldn't read the seen tactics in {}", path.display()))?
+        } else {
+            vec![]
+        };
+        let keep = runs.len().saturating_sub(window);
+        runs.drain(..keep);
+        runs.push(vec![]);
+        Ok(SeenPuzzles { path, runs })
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.runs.iter().flatten().any(|seen| seen == id)
+    }
+
+    /// Adds the ID to the current run and saves the window.
+    pub fn record(&mut self, id: &str) -> Result<()> {
+        if let Some(run) = self.runs.last_mut() {
+            run.push(id.to_string());
+        }
+        fs::write(&self.path, serde_json::to_string(&self.runs)?)
+            .with_context(|| format!("Couldn't save the seen tactics to {}", self.path.display()))
+    }
+}
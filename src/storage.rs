@@ -0,0 +1,21 @@
+use std::{env, fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+
+/// The directory tactics-trainer keeps its files in, creating it if needed. This can be set with
+/// `TACTICS_DATA_DIR`, otherwise it follows `XDG_DATA_HOME`, or `APPDATA` on Windows.
+pub fn data_dir() -> Result<PathBuf> {
+    let dir = match env::var_os("TACTICS_DATA_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+            })
+            .context("Couldn't find a directory to store data in, try setting TACTICS_DATA_DIR")?
+            .join("tactics-trainer"),
+    };
+    fs::create_dir_all(&dir).with_context(|| format!("Couldn't create {}", dir.display()))?;
+    Ok(dir)
+}
@@ -0,0 +1,77 @@
+use std::env;
+
+use clap::ArgEnum;
+use colored::Color;
+
+/// The terminal background to pick board colors for.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Theme {
+    Auto,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    /// Picks a palette with enough contrast for the background. `Auto` goes by the `COLORFGBG`
+    /// variable that some terminals set, ex. "15;0" for white on black, and assumes a dark
+    /// background when it isn't set.
+    pub fn palette(self) -> Palette {
+        let light = match self {
+            Theme::Auto => background_is_light().unwrap_or(false),
+            Theme::Dark => false,
+            Theme::Light => true,
+        };
+        if light {
+            // Dark dots and deep piece colors, which stand out against white
+            Palette {
+                light_square: 110,
+                dark_square: 50,
+                white_piece: Color::TrueColor {
+                    r: 0,
+                    g: 50,
+                    b: 170,
+                },
+                black_piece: Color::TrueColor { r: 160, g: 0, b: 0 },
+                highlight: (250, 230, 140),
+            }
+        } else {
+            // Bright dots and pieces, which stand out against black
+            Palette {
+                light_square: 200,
+                dark_square: 120,
+                white_piece: Color::BrightBlue,
+                black_piece: Color::BrightRed,
+                highlight: (110, 100, 40),
+            }
+        }
+    }
+}
+
+/// Reads the background from `COLORFGBG`, where 7 and 9 to 15 are the light colors of the
+/// 16 color palette.
+fn background_is_light() -> Option<bool> {
+    let colors = env::var("COLORFGBG").ok()?;
+    let background = colors.rsplit(';').next()?.parse::<u8>().ok()?;
+    Some(background == 7 || background >= 9)
+}
+
+/// Colors for drawing boards.
+#[derive(Clone, Copy)]
+pub struct Palette {
+    /// The gray of the dots on empty light squares.
+    pub light_square: u8,
+    /// The gray of the dots on empty dark squares.
+    pub dark_square: u8,
+    pub white_piece: Color,
+    pub black_piece: Color,
+    /// The background of highlighted squares, ex. for a revealed move.
+    pub highlight: (u8, u8, u8),
+}
+
+/// How boards are drawn.
+#[derive(Clone, Copy)]
+pub struct BoardStyle {
+    /// Draw pieces as figurines instead of letters.
+    pub figurines: bool,
+    pub palette: Palette,
+}
@@ -0,0 +1,129 @@
+use anyhow::Result;
+use colored::*;
+
+use crate::api::ChessTactic;
+use crate::i18n::Lang;
+use crate::screen::Screen;
+use crate::{puzzle_position, solve, SolveOptions, Variant};
+
+/// One step of the tutorial: an explanation, optionally followed by a tactic to solve.
+struct Step {
+    en: &'static str,
+    de: &'static str,
+    es: &'static str,
+    /// The FEN and moves of a tactic, in the same format the server sends them.
+    tactic: Option<(&'static str, &'static [&'static str])>,
+}
+
+static STEPS: [Step; 4] = [
+    Step {
+        en: "Welcome to tactics-trainer! Each tactic starts right after your opponent's move. \
+             Your job is to find the best move, and keep finding them until the tactic is over.\n\n\
+             At the prompt you can also type '?' for help, 's' to show the board again, 'f' for \
+             the position in FEN, or 'r' for the tactic's rating.",
+        de: "Willkommen bei tactics-trainer! Jede Aufgabe beginnt direkt nach dem Zug deines \
+             Gegners. Deine Aufgabe ist es, den besten Zug zu finden, und so weiter, bis die \
+             Aufgabe gelöst ist.\n\n\
+             Bei der Eingabe kannst du auch '?' für Hilfe, 's' für das Brett, 'f' für die \
+             Stellung in FEN oder 'r' für die Wertung der Aufgabe eingeben.",
+        es: "¡Bienvenido a tactics-trainer! Cada ejercicio empieza justo después de la jugada \
+             de tu rival. Tu tarea es encontrar la mejor jugada, y seguir así hasta terminar el \
+             ejercicio.\n\n\
+             En la entrada también puedes escribir '?' para la ayuda, 's' para ver el tablero, \
+             'f' para la posición en FEN o 'r' para la puntuación del ejercicio.",
+        tactic: None,
+    },
+    Step {
+        en: "Here's a first tactic. Enter moves in SAN, like Re8 or Nxf7, or with figurines like \
+             ♖e8. If you're not sure, press enter without a move to see the answer.",
+        de: "Hier ist eine erste Aufgabe. Gib Züge in SAN ein, wie Te8 oder Sxf7, oder mit \
+             Figurensymbolen wie ♖e8. Wenn du unsicher bist, drücke Enter ohne Zug, um die \
+             Lösung zu sehen.",
+        es: "Aquí tienes un primer ejercicio. Escribe las jugadas en SAN, como Te8 o Cxf7, o con \
+             figuras como ♖e8. Si no estás seguro, pulsa Intro sin jugada para ver la respuesta.",
+        tactic: Some(("6k1/5ppp/3n4/8/8/8/5PPP/4R1K1 b - - 0 1", &["d6c4", "e1e8"])),
+    },
+    Step {
+        en: "This one takes two moves. If you're stuck, type 'hint' to see which piece to move. \
+             Hints cost points, and --hints gives you a budget for a whole run.",
+        de: "Diese Aufgabe braucht zwei Züge. Wenn du nicht weiterkommst, gib 'hint' ein, um zu \
+             sehen, welche Figur ziehen soll. Hinweise kosten Punkte, und mit --hints legst du \
+             ein Budget für einen ganzen Durchlauf fest.",
+        es: "Este necesita dos jugadas. Si te atascas, escribe 'hint' para ver qué pieza mover. \
+             Las pistas cuestan puntos, y --hints te da un presupuesto para toda la sesión.",
+        tactic: Some((
+            "6k1/5ppp/8/2qN4/8/8/5PPP/6K1 b - - 0 1",
+            &["c5c8", "d5e7", "g8h8", "e7c8"],
+        )),
+    },
+    Step {
+        en: "That's it! Some ways to shape your training:\n\
+             \x20 --tags fork, or --filter \"fork & !mateIn1\", to pick themes\n\
+             \x20 --rating 1200-1800 to pick a difficulty\n\
+             \x20 --square-input to enter moves as squares\n\
+             \x20 --pin-board to keep the board in place\n\
+             \x20 --name-the-theme to name the motif before solving\n\
+             Type 'n' at the prompt to skip a tactic. Run tactics-trainer to get started.",
+        de: "Das war's! Ein paar Möglichkeiten, dein Training anzupassen:\n\
+             \x20 --tags fork oder --filter \"fork & !mateIn1\", um Motive auszuwählen\n\
+             \x20 --rating 1200-1800, um die Schwierigkeit zu wählen\n\
+             \x20 --square-input, um Züge als Felder einzugeben\n\
+             \x20 --pin-board, um das Brett an seinem Platz zu halten\n\
+             \x20 --name-the-theme, um vor dem Lösen das Motiv zu benennen\n\
+             Gib 'n' ein, um eine Aufgabe zu überspringen. Starte tactics-trainer, um loszulegen.",
+        es: "¡Eso es todo! Algunas formas de ajustar tu entrenamiento:\n\
+             \x20 --tags fork, o --filter \"fork & !mateIn1\", para elegir temas\n\
+             \x20 --rating 1200-1800 para elegir la dificultad\n\
+             \x20 --square-input para introducir jugadas como casillas\n\
+             \x20 --pin-board para mantener el tablero en su sitio\n\
+             \x20 --name-the-theme para nombrar el motivo antes de resolver\n\
+             Escribe 'n' para saltar un ejercicio. Ejecuta tactics-trainer para empezar.",
+        tactic: None,
+    },
+];
+
+impl Step {
+    fn text(&self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::En => self.en,
+            Lang::De => self.de,
+            Lang::Es => self.es,
+        }
+    }
+}
+
+/// Walks through the prompt commands and move entry, solving a couple of embedded tactics with
+/// the regular solve loop.
+pub fn run(lang: Lang, screen: &mut Screen) -> Result<()> {
+    let mut options = SolveOptions {
+        name_the_theme: false,
+        hints_left: None,
+        square_input: false,
+        variant: Variant::Standard,
+        streak: None,
+    };
+    for (number, step) in STEPS.iter().enumerate() {
+        // Through the screen, so a pinned board's redraw keeps the explanation up
+        screen.gap();
+        screen.message(format!("{}/{}", number + 1, STEPS.len()).bold().to_string());
+        screen.message(step.text(lang).to_string());
+        if let Some((fen, moves)) = step.tactic {
+            let tactic = ChessTactic {
+                id: format!("tutorial-{}", number + 1),
+                moves: moves.iter().map(|m| m.to_string()).collect(),
+                fen: fen.to_string(),
+                popularity: 0,
+                tags: vec![],
+                game_link: String::new(),
+                rating: 600,
+                rating_deviation: 0,
+                number_plays: 0,
+                solver_moves_first: Some(false),
+            };
+            let position = puzzle_position(&tactic, Variant::Standard)?;
+            screen.show_board(&position);
+            solve(&tactic, position, lang, screen, &mut options)?;
+        }
+    }
+    Ok(())
+}
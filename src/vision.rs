@@ -0,0 +1,119 @@
+use anyhow::Result;
+use shakmaty::{Chess, Color, Position, Square};
+
+use crate::i18n::Lang;
+use crate::rng::Rng;
+use crate::screen::Screen;
+
+enum Question {
+    Attackers { square: Square, side: Color },
+    Defended { square: Square },
+}
+
+enum Answer {
+    Count(usize),
+    YesNo(bool),
+}
+
+impl Question {
+    fn prompt(&self, position: &Chess, lang: Lang) -> String {
+        match self {
+            Question::Attackers { square, side } => lang.attackers_question(*side, *square),
+            Question::Defended { square } => {
+                let piece = position.board().piece_at(*square).unwrap();
+                lang.defended_question(piece.color, piece.role, *square)
+            }
+        }
+    }
+
+    fn answer(&self, position: &Chess) -> Answer {
+        let board = position.board();
+        match self {
+            Question::Attackers { square, side } => {
+                Answer::Count(board.attacks_to(*square, *side, board.occupied()).count())
+            }
+            Question::Defended { square } => {
+                let color = board.piece_at(*square).unwrap().color;
+                Answer::YesNo(board.attacks_to(*square, color, board.occupied()).any())
+            }
+        }
+    }
+}
+
+impl Answer {
+    fn matches(&self, reply: &str, lang: Lang) -> bool {
+        match self {
+            Answer::Count(count) => reply.parse::<usize>().ok() == Some(*count),
+            Answer::YesNo(answer) => lang.parse_yes_no(reply) == Some(*answer),
+        }
+    }
+
+    fn display(&self, lang: Lang) -> String {
+        match self {
+            Answer::Count(count) => count.to_string(),
+            Answer::YesNo(answer) => lang.yes_no(*answer).to_string(),
+        }
+    }
+}
+
+/// Quizzes the user about attackers and defenders in the given position, checking each answer
+/// against shakmaty's attack tables.
+pub fn drill(
+    position: &Chess,
+    questions: usize,
+    lang: Lang,
+    screen: &mut Screen,
+    rng: &mut Rng,
+) -> Result<()> {
+    let mut correct = 0;
+    for _ in 0..questions {
+        let question = random_question(position, rng);
+        let answer = question.answer(position);
+        loop {
+            screen.gap();
+            let reply = screen.prompt(&question.prompt(position, lang))?;
+            let reply = reply.trim().to_lowercase();
+            match reply.as_ref() {
+                "s" | "show" => {
+                    screen.print_board(position);
+                    continue;
+                }
+                "" => screen.message(lang.answer_was(&answer.display(lang))),
+                x => {
+                    if answer.matches(x, lang) {
+                        correct += 1;
+                        screen.message(lang.correct().to_string());
+                    } else {
+                        screen.message(lang.wrong_answer(x, &answer.display(lang)));
+                    }
+                }
+            }
+            break;
+        }
+    }
+    screen.gap();
+    screen.message(lang.answered_correctly(correct, questions));
+    Ok(())
+}
+
+fn random_question(position: &Chess, rng: &mut Rng) -> Question {
+    let board = position.board();
+    let pieces = (board.occupied() & !board.kings())
+        .into_iter()
+        .collect::<Vec<Square>>();
+    if rng.below(2) == 0 && !pieces.is_empty() {
+        Question::Defended {
+            square: pieces[rng.below(pieces.len())],
+        }
+    } else {
+        let side = if rng.below(2) == 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        Question::Attackers {
+            square: Square::new(rng.below(64) as u32),
+            side,
+        }
+    }
+}